@@ -1,14 +1,29 @@
 use bencher::*;
 
+use fair::card::{hand, Card};
+use fair::ProvablyFairConfig;
+
 fn bench_simulate_games(b: &mut Bencher) {
     let client_seed = "some client seed";
     let server_seed = "some server seed";
     let mut nonce = 0;
     b.iter(|| {
         nonce += 1;
-        fair::games::baccarat::simulate(client_seed, server_seed, nonce)
+        let config = ProvablyFairConfig::new(client_seed, server_seed, nonce);
+        fair::games::baccarat::simulate(config)
+    });
+}
+
+fn bench_evaluate_poker_hands(b: &mut Bencher) {
+    let hands: Vec<Vec<Card>> = (0..52)
+        .map(|i| (i..i + 7).map(|j| Card::at_index(j * 5 % 52)).collect())
+        .collect();
+    let mut i = 0;
+    b.iter(|| {
+        i = (i + 1) % hands.len();
+        hand::evaluate(&hands[i])
     });
 }
 
-benchmark_group!(benches, bench_simulate_games);
+benchmark_group!(benches, bench_simulate_games, bench_evaluate_poker_hands);
 benchmark_main!(benches);
@@ -1,5 +1,8 @@
 import * as wasm from "../pkg/index.js";
 
+// `new ProvablyFairRNG(clientSeed, serverSeed, nonce)` to verify games this library doesn't support
+export { ProvablyFairRNG } from "../pkg/index.js";
+
 export const gameTypes = {
   SINGLEPLAYER: "SINGLEPLAYER",
   MULTIPLAYER: "MULTIPLAYER",
@@ -8,12 +11,33 @@ export const gameTypes = {
 const { SINGLEPLAYER, MULTIPLAYER } = gameTypes;
 
 export const allGames = [
-  { id: "baccarat", displayName: "Baccarat", type: SINGLEPLAYER },
-  { id: "blackjack", displayName: "Blackjack", type: SINGLEPLAYER },
+  {
+    id: "baccarat",
+    displayName: "Baccarat",
+    type: SINGLEPLAYER,
+    defaultOpts: { decks: 0 },
+  },
+  {
+    id: "blackjack",
+    displayName: "Blackjack",
+    type: SINGLEPLAYER,
+    defaultOpts: { decks: 0 },
+  },
   { id: "crash", displayName: "Crash", type: MULTIPLAYER },
   { id: "diamond_poker", displayName: "Diamond Poker", type: SINGLEPLAYER },
   { id: "dice", displayName: "Dice", type: SINGLEPLAYER },
-  { id: "hilo", displayName: "Hilo", type: SINGLEPLAYER },
+  {
+    id: "dragon_tower",
+    displayName: "Dragon Tower",
+    type: SINGLEPLAYER,
+    defaultOpts: { difficulty: "easy" },
+  },
+  {
+    id: "hilo",
+    displayName: "Hilo",
+    type: SINGLEPLAYER,
+    defaultOpts: { decks: 0 },
+  },
   { id: "keno", displayName: "Keno", type: SINGLEPLAYER },
   { id: "limbo", displayName: "Limbo", type: SINGLEPLAYER },
   {
@@ -38,7 +62,12 @@ export const allGames = [
     type: SINGLEPLAYER,
     defaultOpts: { round: 0 },
   },
-  { id: "video_poker", displayName: "Video Poker", type: SINGLEPLAYER },
+  {
+    id: "video_poker",
+    displayName: "Video Poker",
+    type: SINGLEPLAYER,
+    defaultOpts: { decks: 0 },
+  },
   {
     id: "wheel",
     displayName: "Wheel",
@@ -65,6 +94,56 @@ export function simulateSingleplayer(gameId, opts = {}) {
   return wasm.simulate(gameId, clientSeed, serverSeed, nonce, otherOpts);
 }
 
+// results of `count` consecutive nonces from `startNonce`, in a single wasm call
+export function simulateRange(gameId, opts = {}) {
+  const { clientSeed, serverSeed, startNonce, count, ...otherOpts } = opts;
+  return wasm.simulate_range(
+    gameId,
+    clientSeed,
+    serverSeed,
+    startNonce,
+    count,
+    otherOpts
+  );
+}
+
+// results of a list of nonces, in a single wasm call
+export function simulateNonces(gameId, opts = {}) {
+  const { clientSeed, serverSeed, nonces, ...otherOpts } = opts;
+  return wasm.simulate_nonces(
+    gameId,
+    clientSeed,
+    serverSeed,
+    Float64Array.from(nonces),
+    otherOpts
+  );
+}
+
+// Float64Array of the outcome (roll, multiplier, pocket or payout) of each nonce
+export function outcomesRange(gameId, opts = {}) {
+  const { clientSeed, serverSeed, startNonce, count, ...otherOpts } = opts;
+  return wasm.outcomes_range(
+    gameId,
+    clientSeed,
+    serverSeed,
+    startNonce,
+    count,
+    otherOpts
+  );
+}
+
+// outcome of the tiles `picks` picked from the bottom row up, and the multiplier of cashing out
+export function dragonTowerClimb(opts = {}) {
+  const { clientSeed, serverSeed, nonce, picks, ...otherOpts } = opts;
+  return wasm.dragon_tower_climb(
+    clientSeed,
+    serverSeed,
+    nonce,
+    otherOpts,
+    Uint8Array.from(picks)
+  );
+}
+
 export function simulateMultiplayer(gameId, opts = {}) {
   const { gameHash, ...otherOpts } = opts;
   return wasm.simulate_multiplayer(gameId, gameHash, otherOpts);
@@ -77,3 +156,37 @@ export function hashServerSeed(serverSeed) {
 export function verifyGameHashStake(gameHash) {
   return wasm.verify_game_hash_stake(gameHash);
 }
+
+// verifies a crash game hash against any hash chain without freezing the page: `stepsPerFrame`
+// hashes are walked per animation frame (or timer tick in a worker) and `onProgress(walked,
+// total)` is called after each, resolves to whether the game hash is part of the chain
+export function verifyGameHash(gameHash, opts = {}) {
+  const { config, stepsPerFrame = 100000, onProgress } = opts;
+  const nextFrame =
+    typeof requestAnimationFrame === "function"
+      ? requestAnimationFrame
+      : (callback) => setTimeout(callback, 0);
+
+  return new Promise((resolve, reject) => {
+    // an invalid hash or config rejects the promise
+    const verifier = new wasm.CrashVerifier(gameHash, config);
+    const frame = () => {
+      try {
+        const result = verifier.step(stepsPerFrame);
+        if (onProgress) {
+          onProgress(verifier.walked, verifier.total);
+        }
+        if (result === undefined) {
+          nextFrame(frame);
+        } else {
+          verifier.free();
+          resolve(result);
+        }
+      } catch (e) {
+        verifier.free();
+        reject(e);
+      }
+    };
+    frame();
+  });
+}
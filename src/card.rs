@@ -1,7 +1,24 @@
-use serde::Serialize;
+//! # cards
+//!
+//! Cards as dealt by the card games, and dealing from an infinite deck or a finite `Shoe`.
+//!
+//! Cards parse from the usual notations and display with suit symbols, or ASCII with `{:#}`:
+//!
+//! ```
+//! use fair::card::Card;
+//!
+//! let card: Card = "10h".parse().unwrap();
+//! assert_eq!(card, "♥10".parse().unwrap());
+//! assert_eq!(format!("{} {:#}", card, card), "♥10 10H");
+//! ```
+
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
+
+use crate::rng::{ProvablyFairConfig, ProvablyFairRNG};
 
-use crate::rng::ProvablyFairRNG;
+pub mod hand;
 use CardRank::*;
 use CardSuite::*;
 
@@ -11,7 +28,9 @@ pub const CARD_RANK_ORDER: &[CardRank; 13] = &[
 
 pub const CARD_SUITE_ORDER: &[CardSuite; 4] = &[DIAMOND, HEART, SPADE, CLUB];
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+/// Ranks are ordered from two to ace.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum CardRank {
     TWO,
     THREE,
@@ -29,7 +48,8 @@ pub enum CardRank {
 }
 
 impl CardRank {
-    fn to_int(&self) -> u8 {
+    /// 2 to 10, then 11 to 14 for jack, queen, king and ace.
+    pub fn to_int(self) -> u8 {
         match self {
             TWO => 2,
             THREE => 3,
@@ -62,40 +82,128 @@ impl fmt::Display for CardRank {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+impl FromStr for CardRank {
+    type Err = String;
+
+    /// `2` to `10` (or `T`), `J`, `Q`, `K` and `A`, in any case.
+    fn from_str(s: &str) -> Result<CardRank, String> {
+        let rank = match s.to_uppercase().as_str() {
+            "J" => JACK,
+            "Q" => QUEEN,
+            "K" => KING,
+            "A" => ACE,
+            "T" => TEN,
+            n => match n.parse::<usize>() {
+                Ok(n @ 2..=10) => CARD_RANK_ORDER[n - 2],
+                _ => return Err(format!("invalid card rank {}", s)),
+            },
+        };
+        Ok(rank)
+    }
+}
+
+/// Suits are ordered like Stake's deck, see `CARD_SUITE_ORDER`.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum CardSuite {
     DIAMOND,
-    CLUB,
-    SPADE,
     HEART,
+    SPADE,
+    CLUB,
 }
 
+/// `♦`, or `D` with `{:#}`.
 impl fmt::Display for CardSuite {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match self {
-            DIAMOND => "♦",
-            CLUB => "♣",
-            SPADE => "♠",
-            HEART => "♥",
+        let s = match (self, f.alternate()) {
+            (DIAMOND, false) => "♦",
+            (HEART, false) => "♥",
+            (SPADE, false) => "♠",
+            (CLUB, false) => "♣",
+            (DIAMOND, true) => "D",
+            (HEART, true) => "H",
+            (SPADE, true) => "S",
+            (CLUB, true) => "C",
         };
         write!(f, "{}", s)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+impl FromStr for CardSuite {
+    type Err = String;
+
+    /// `♦♣♠♥` (or their outlined variants `♢♧♤♡`) and `D`, `C`, `S`, `H` in any case.
+    fn from_str(s: &str) -> Result<CardSuite, String> {
+        let suite = match s {
+            "♦" | "♢" | "D" | "d" => DIAMOND,
+            "♣" | "♧" | "C" | "c" => CLUB,
+            "♠" | "♤" | "S" | "s" => SPADE,
+            "♥" | "♡" | "H" | "h" => HEART,
+            _ => return Err(format!("invalid card suit {}", s)),
+        };
+        Ok(suite)
+    }
+}
+
+/// Cards are ordered by rank, then by suit, like their `index` in Stake's deck.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Card {
     rank: CardRank,
     suite: CardSuite,
 }
 
+/// `♠J`, or `JS` with `{:#}` for terminals without Unicode.
 impl fmt::Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}", self.suite, self.rank)
+        if f.alternate() {
+            write!(f, "{}{:#}", self.rank, self.suite)
+        } else {
+            write!(f, "{}{}", self.suite, self.rank)
+        }
+    }
+}
+
+impl FromStr for Card {
+    type Err = String;
+
+    /// A rank and a suit in either order, e.g. `AS`, `10h`, `Td` or `♠J`.
+    fn from_str(s: &str) -> Result<Card, String> {
+        let s = s.trim();
+        let invalid = || {
+            format!(
+                "invalid card {:?}: expected a rank (2-10, J, Q, K or A) and a suit (S, H, D, C \
+                 or ♠♥♦♣), e.g. AS, 10h or ♠J",
+                s
+            )
+        };
+        let first = s.chars().next().ok_or_else(invalid)?;
+        let last = s.chars().next_back().ok_or_else(invalid)?;
+        let (rank, suite) = match last.to_string().parse() {
+            Ok(suite) => (&s[..s.len() - last.len_utf8()], suite),
+            Err(_) => (
+                &s[first.len_utf8()..],
+                first.to_string().parse().map_err(|_| invalid())?,
+            ),
+        };
+        let rank = rank.parse().map_err(|_| invalid())?;
+        Ok(Card::new(rank, suite))
     }
 }
 
 // draw random card from an inifinite deck
 impl Card {
+    pub fn new(rank: CardRank, suite: CardSuite) -> Card {
+        Card { rank, suite }
+    }
+
+    pub fn rank(&self) -> CardRank {
+        self.rank
+    }
+
+    pub fn suite(&self) -> CardSuite {
+        self.suite
+    }
+
     // deterministically returns a random card using rng
     pub fn random(rng: &mut ProvablyFairRNG<f64>) -> Card {
         let val = rng.next().unwrap();
@@ -103,20 +211,32 @@ impl Card {
         Card::at_index(idx)
     }
 
-    // Returns a card from a virtual ordered deck of 52 cards
-    // the order is the following: ♦2, ♥2, ♠2, ♣2, ♦3, ♥3, ♠3...
-    fn at_index(idx: usize) -> Card {
+    /// Returns a card from a virtual ordered deck of 52 cards, in the following order: ♦2, ♥2,
+    /// ♠2, ♣2, ♦3, ♥3, ♠3... Panics if `idx` is 52 or more.
+    pub fn at_index(idx: usize) -> Card {
         let suite_idx = idx % 4;
         let suite = CARD_SUITE_ORDER[suite_idx];
 
         let rank_idx = idx / 4;
         let rank = CARD_RANK_ORDER[rank_idx];
-        let card = Card { suite, rank };
-        card
+        Card { suite, rank }
+    }
+
+    /// Inverse of `at_index`.
+    pub fn index(self) -> usize {
+        let rank_idx = CARD_RANK_ORDER
+            .iter()
+            .position(|&r| r == self.rank)
+            .unwrap();
+        let suite_idx = CARD_SUITE_ORDER
+            .iter()
+            .position(|&s| s == self.suite)
+            .unwrap();
+        rank_idx * 4 + suite_idx
     }
 
     // returns baccarat value of card
-    pub fn to_baccarat_value(&self) -> u8 {
+    pub fn to_baccarat_value(self) -> u8 {
         let rank = self.rank;
         match rank {
             ACE => 1,
@@ -126,24 +246,55 @@ impl Card {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct Deck {
     cards: Vec<Card>,
 }
 
+/// `♠J - ♥10`, or `JS - 10H` with `{:#}`.
 impl fmt::Display for Deck {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = self
             .cards
             .iter()
-            .map(|c| c.to_string())
+            .map(|c| {
+                if f.alternate() {
+                    format!("{:#}", c)
+                } else {
+                    c.to_string()
+                }
+            })
             .collect::<Vec<String>>()
             .join(" - ");
         write!(f, "{}", s)
     }
 }
 
+impl FromStr for Deck {
+    type Err = String;
+
+    /// Cards separated by spaces, commas or dashes, e.g. `AS KS QS` or `♠J - ♥10`.
+    fn from_str(s: &str) -> Result<Deck, String> {
+        let cards = s
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '-')
+            .filter(|card| !card.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Deck { cards })
+    }
+}
+
+impl From<Vec<Card>> for Deck {
+    fn from(cards: Vec<Card>) -> Deck {
+        Deck { cards }
+    }
+}
+
 impl Deck {
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
     pub fn from_rng(rng: &mut ProvablyFairRNG<f64>, count: u32) -> Deck {
         let mut cards: Vec<Card> = vec![];
         for _ in 0..count {
@@ -155,6 +306,105 @@ impl Deck {
     }
 }
 
+/// Largest shoe, operators deal from 6 or 8 decks.
+pub const MAX_DECKS: u8 = 8;
+
+/// How the cards of a game are dealt.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Dealing {
+    /// Every card is drawn from a full deck, like on Stake.
+    #[default]
+    Infinite,
+    /// Cards are dealt without replacement from a shoe of this many decks, see `Shoe`.
+    Shoe(u8),
+}
+
+impl Dealing {
+    /// `0` deals from an infinite deck, otherwise from a shoe of `decks` decks.
+    pub fn from_decks(decks: u8) -> Result<Dealing, String> {
+        match decks {
+            0 => Ok(Dealing::Infinite),
+            1..=MAX_DECKS => Ok(Dealing::Shoe(decks)),
+            _ => Err(format!(
+                "decks must be between 0 (infinite deck) and {} inclusive",
+                MAX_DECKS
+            )),
+        }
+    }
+}
+
+/// Decks shuffled together once with the RNG, then dealt from the top without replacement.
+///
+/// Stake deals from an infinite deck (see `Dealing::Infinite`), so no operator's verifier covers
+/// shoes. The shuffle follows the pick-and-remove method Stake uses for the squares of Keno and
+/// Mines instead: each card is drawn from the remaining ordered decks (see `Card::at_index`) at
+/// `float * remaining cards`, consecutive floats for consecutive cards.
+#[derive(Debug)]
+pub struct Shoe {
+    cards: Vec<Card>,
+    dealt: usize,
+}
+
+impl Shoe {
+    pub fn new(rng: &mut ProvablyFairRNG<f64>, decks: u8) -> Shoe {
+        let mut remaining: Vec<Card> = (0..decks as usize * 52)
+            .map(|idx| Card::at_index(idx % 52))
+            .collect();
+        let cards = (0..remaining.len())
+            .map(|_| {
+                let f = rng.next().unwrap();
+                let idx = (f * remaining.len() as f64) as usize;
+                remaining.remove(idx)
+            })
+            .collect();
+        Shoe { cards, dealt: 0 }
+    }
+
+    pub fn deal(&mut self) -> Option<Card> {
+        let card = *self.cards.get(self.dealt)?;
+        self.dealt += 1;
+        Some(card)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.cards.len() - self.dealt
+    }
+}
+
+/// Deals the cards of a game as chosen by its `Dealing`.
+pub struct Dealer {
+    rng: ProvablyFairRNG<f64>,
+    shoe: Option<Shoe>,
+}
+
+impl Dealer {
+    pub fn new(config: ProvablyFairConfig, dealing: Dealing) -> Dealer {
+        let mut rng = ProvablyFairRNG::from_config(config);
+        let shoe = match dealing {
+            Dealing::Infinite => None,
+            Dealing::Shoe(decks) => Some(Shoe::new(&mut rng, decks)),
+        };
+        Dealer { rng, shoe }
+    }
+
+    /// Panics if the shoe is empty, a single deck has enough cards for any game.
+    pub fn deal(&mut self) -> Card {
+        match &mut self.shoe {
+            Some(shoe) => shoe.deal().expect("no cards left in the shoe"),
+            None => Card::random(&mut self.rng),
+        }
+    }
+
+    pub fn deal_deck(&mut self, count: u32) -> Deck {
+        match self.shoe {
+            Some(_) => Deck {
+                cards: (0..count).map(|_| self.deal()).collect(),
+            },
+            None => Deck::from_rng(&mut self.rng, count),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -175,8 +425,100 @@ mod test {
             "♦9", "♥9", "♠9", "♣9", "♦10", "♥10", "♠10", "♣10", "♦J", "♥J", "♠J", "♣J", "♦Q", "♥Q",
             "♠Q", "♣Q", "♦K", "♥K", "♠K", "♣K", "♦A", "♥A", "♠A", "♣A",
         ];
+        for (i, expected) in expected_deck_order.iter().enumerate() {
+            assert_eq!(Card::at_index(i).to_string(), *expected);
+            assert_eq!(Card::at_index(i).index(), i);
+        }
+    }
+
+    #[test]
+    fn parse_card() {
+        let jack_of_spades = Card::new(JACK, SPADE);
+        for s in &["JS", "js", "♠J", "J♠", "♤j", " JS\n"] {
+            assert_eq!(s.parse(), Ok(jack_of_spades), "{}", s);
+        }
+        assert_eq!("10h".parse(), Ok(Card::new(TEN, HEART)));
+        assert_eq!("Td".parse(), Ok(Card::new(TEN, DIAMOND)));
+        assert_eq!("AC".parse(), Ok(Card::new(ACE, CLUB)));
+        for s in &["", "S", "1S", "11S", "JX", "♠", "JSS"] {
+            assert!(s.parse::<Card>().is_err(), "{}", s);
+        }
+
         for i in 0..52 {
-            assert_eq!(Card::at_index(i).to_string(), expected_deck_order[i]);
+            let card = Card::at_index(i);
+            assert_eq!(card.to_string().parse(), Ok(card));
+            assert_eq!(format!("{:#}", card).parse(), Ok(card));
         }
     }
+
+    #[test]
+    fn parse_deck() {
+        let deck: Deck = "♠J - ♥10 - ♥5".parse().unwrap();
+        assert_eq!(deck.to_string(), "♠J - ♥10 - ♥5");
+        assert_eq!(format!("{:#}", deck), "JS - 10H - 5H");
+        assert_eq!("JS,10h 5H".parse(), Ok(deck.clone()));
+        assert_eq!(deck.cards()[0].rank(), JACK);
+
+        let json = serde_json::to_string(&deck).unwrap();
+        assert_eq!(serde_json::from_str::<Deck>(&json).unwrap(), deck);
+    }
+
+    #[test]
+    fn card_order() {
+        let mut cards: Vec<Card> = "AS 2H KD 2C".parse::<Deck>().unwrap().cards().to_vec();
+        cards.sort();
+        assert_eq!(Deck::from(cards).to_string(), "♥2 - ♣2 - ♦K - ♠A");
+
+        // sorting agrees with the order of Stake's deck
+        let mut cards: Vec<Card> = (0..52).rev().map(Card::at_index).collect();
+        cards.sort();
+        assert!(cards
+            .iter()
+            .enumerate()
+            .all(|(idx, card)| card.index() == idx));
+    }
+
+    #[test]
+    fn test_dealing() {
+        assert_eq!(Dealing::from_decks(0), Ok(Dealing::Infinite));
+        assert_eq!(Dealing::from_decks(6), Ok(Dealing::Shoe(6)));
+        assert!(Dealing::from_decks(9).is_err());
+    }
+
+    #[test]
+    fn test_shoe() {
+        let mut rng = ProvablyFairRNG::new("client seed", "server seed", 1);
+        let mut shoe = Shoe::new(&mut rng, 2);
+        assert_eq!(shoe.remaining(), 104);
+        let mut counts = [0; 52];
+        while let Some(card) = shoe.deal() {
+            counts[card.index()] += 1;
+        }
+        // every card of both decks is dealt exactly once
+        assert_eq!(counts, [2; 52]);
+        assert_eq!(shoe.remaining(), 0);
+
+        // golden deal: the first float 0.7467 picks card 232 of the shoe, a ♦8
+        let deal = |nonce| {
+            let mut dealer = Dealer::new(
+                ProvablyFairConfig::new("client seed", "server seed", nonce),
+                Dealing::Shoe(6),
+            );
+            dealer.deal_deck(10).to_string()
+        };
+        assert_eq!(deal(1), "♦8 - ♣Q - ♦9 - ♣7 - ♦7 - ♣5 - ♦2 - ♠Q - ♠10 - ♥J");
+        assert_ne!(deal(1), deal(2));
+    }
+
+    #[test]
+    fn test_infinite_dealer() {
+        let config = ProvablyFairConfig::new("client seed", "server seed", 1);
+        let mut dealer = Dealer::new(config, Dealing::Infinite);
+        let mut rng = ProvablyFairRNG::new("client seed", "server seed", 1);
+        assert_eq!(dealer.deal(), Card::random(&mut rng));
+        assert_eq!(
+            dealer.deal_deck(3).to_string(),
+            Deck::from_rng(&mut rng, 3).to_string()
+        );
+    }
 }
@@ -0,0 +1,351 @@
+//! # poker hands
+//!
+//! Evaluates the best poker hand among 5 to 7 cards, e.g. a video poker hand or Texas Hold'em
+//! hole cards with the board. Hands rank by category, then by the ranks that decide ties
+//! between hands of the same category, so comparing two `HandRank` is enough to find the winner:
+//!
+//! ```
+//! use fair::card::hand::{evaluate, Category};
+//!
+//! let board = "2S 7H QD QS 10C".parse::<fair::card::Deck>().unwrap();
+//! let mut alice = board.cards().to_vec();
+//! alice.extend(&["QH".parse().unwrap(), "3C".parse().unwrap()]);
+//! let mut bob = board.cards().to_vec();
+//! bob.extend(&["AS".parse().unwrap(), "7C".parse().unwrap()]);
+//!
+//! let alice = evaluate(&alice).unwrap();
+//! let bob = evaluate(&bob).unwrap();
+//! assert_eq!(alice.category(), Category::ThreeOfAKind);
+//! assert_eq!(bob.to_string(), "Two Pair: Q, 7, A");
+//! assert!(alice > bob);
+//! ```
+//!
+//! Cards dealt from an infinite deck may repeat, which is why five of a kind exists.
+
+use super::{Card, CardRank, CARD_RANK_ORDER};
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::fmt;
+
+pub const MIN_CARDS: usize = 5;
+pub const MAX_CARDS: usize = 7;
+
+/// Hand categories, from the weakest to the strongest.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize)]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Category::HighCard => "High Card",
+            Category::Pair => "Pair",
+            Category::TwoPair => "Two Pair",
+            Category::ThreeOfAKind => "Three Of A Kind",
+            Category::Straight => "Straight",
+            Category::Flush => "Flush",
+            Category::FullHouse => "Full House",
+            Category::FourOfAKind => "Four Of A Kind",
+            Category::StraightFlush => "Straight Flush",
+            Category::FiveOfAKind => "Five Of A Kind",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Strength of a poker hand, hands compare by category then by `kickers`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct HandRank {
+    category: Category,
+    // only the first `len` ranks are meaningful, `len` only depends on the category
+    ranks: [CardRank; 5],
+    len: usize,
+}
+
+impl HandRank {
+    fn new(category: Category) -> HandRank {
+        HandRank {
+            category,
+            ranks: [CardRank::TWO; 5],
+            len: 0,
+        }
+    }
+
+    fn push(mut self, rank: usize) -> HandRank {
+        self.ranks[self.len] = CARD_RANK_ORDER[rank];
+        self.len += 1;
+        self
+    }
+
+    pub fn category(&self) -> Category {
+        self.category
+    }
+
+    /// Ranks deciding between hands of the same category, from the most significant, e.g. the
+    /// rank of the three of a kind then of the pair for a full house, or the highest card of a
+    /// straight (a five for `A 2 3 4 5`).
+    pub fn kickers(&self) -> &[CardRank] {
+        &self.ranks[..self.len]
+    }
+}
+
+/// `Full House: K, 10`, category then kickers.
+impl fmt::Display for HandRank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kickers = self
+            .kickers()
+            .iter()
+            .map(|rank| rank.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}: {}", self.category, kickers)
+    }
+}
+
+impl Serialize for HandRank {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("HandRank", 2)?;
+        state.serialize_field("category", &self.category)?;
+        state.serialize_field("kickers", self.kickers())?;
+        state.end()
+    }
+}
+
+/// Returns the best five card hand among `cards`, which must hold between 5 and 7 cards.
+pub fn evaluate(cards: &[Card]) -> Result<HandRank, String> {
+    if cards.len() < MIN_CARDS || cards.len() > MAX_CARDS {
+        return Err(format!(
+            "a poker hand has between {} and {} cards, got {}",
+            MIN_CARDS,
+            MAX_CARDS,
+            cards.len()
+        ));
+    }
+
+    // rank indexes go from 0 for two to 12 for ace
+    let mut counts = [0u8; 13];
+    let mut ranks = 0u16;
+    let mut suit_counts = [0u8; 4];
+    let mut suit_ranks = [0u16; 4];
+    for card in cards {
+        let rank = card.rank() as usize;
+        let suite = card.suite() as usize;
+        counts[rank] += 1;
+        ranks |= 1 << rank;
+        suit_counts[suite] += 1;
+        suit_ranks[suite] |= 1 << rank;
+    }
+
+    let flush = (0..4).find(|&suite| suit_counts[suite] >= 5);
+    if let Some(high) = flush.and_then(|suite| straight(suit_ranks[suite])) {
+        return Ok(HandRank::new(Category::StraightFlush).push(high));
+    }
+    if let Some(rank) = highest(&counts, 5, None) {
+        return Ok(HandRank::new(Category::FiveOfAKind).push(rank));
+    }
+    if let Some(rank) = highest(&counts, 4, None) {
+        let hand = HandRank::new(Category::FourOfAKind).push(rank);
+        return Ok(kickers(hand, &counts, &[rank], 1));
+    }
+    let three = highest(&counts, 3, None);
+    if let Some(three) = three {
+        if let Some(pair) = highest(&counts, 2, Some(three)) {
+            return Ok(HandRank::new(Category::FullHouse).push(three).push(pair));
+        }
+    }
+    if let Some(suite) = flush {
+        // cards of an infinite deck may repeat, so count them instead of using the rank mask
+        let mut hand = HandRank::new(Category::Flush);
+        let mut suited: Vec<usize> = cards
+            .iter()
+            .filter(|card| card.suite() as usize == suite)
+            .map(|card| card.rank() as usize)
+            .collect();
+        suited.sort_unstable_by(|a, b| b.cmp(a));
+        for &rank in &suited[..5] {
+            hand = hand.push(rank);
+        }
+        return Ok(hand);
+    }
+    if let Some(high) = straight(ranks) {
+        return Ok(HandRank::new(Category::Straight).push(high));
+    }
+    if let Some(three) = three {
+        let hand = HandRank::new(Category::ThreeOfAKind).push(three);
+        return Ok(kickers(hand, &counts, &[three], 2));
+    }
+    if let Some(pair) = highest(&counts, 2, None) {
+        if let Some(second) = highest(&counts, 2, Some(pair)) {
+            let hand = HandRank::new(Category::TwoPair).push(pair).push(second);
+            return Ok(kickers(hand, &counts, &[pair, second], 1));
+        }
+        let hand = HandRank::new(Category::Pair).push(pair);
+        return Ok(kickers(hand, &counts, &[pair], 3));
+    }
+    Ok(kickers(HandRank::new(Category::HighCard), &counts, &[], 5))
+}
+
+// highest rank appearing at least `count` times, other than `except`
+fn highest(counts: &[u8; 13], count: u8, except: Option<usize>) -> Option<usize> {
+    (0..13)
+        .rev()
+        .find(|&rank| counts[rank] >= count && Some(rank) != except)
+}
+
+// adds the `n` highest ranks that aren't part of the hand's combination
+fn kickers(mut hand: HandRank, counts: &[u8; 13], used: &[usize], n: usize) -> HandRank {
+    let mut ranks = (0..13)
+        .rev()
+        .filter(|rank| counts[*rank] > 0 && !used.contains(rank));
+    for _ in 0..n {
+        hand = hand.push(ranks.next().unwrap());
+    }
+    hand
+}
+
+// highest card of the best straight in a mask of ranks, aces also count as ones
+fn straight(ranks: u16) -> Option<usize> {
+    // shift by one so that bit 0 is the ace below the two
+    let ranks = (ranks << 1) | (ranks >> 12 & 1);
+    (4..14)
+        .rev()
+        .find(|&high| (ranks >> (high - 4)) & 0x1f == 0x1f)
+        .map(|high| high - 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::card::{CardRank::*, Deck};
+
+    fn eval(cards: &str) -> HandRank {
+        let deck: Deck = cards.parse().unwrap();
+        evaluate(deck.cards()).unwrap()
+    }
+
+    #[test]
+    fn evaluate_categories() {
+        let hands = [
+            (
+                "2S 7H QD 9S 10C",
+                Category::HighCard,
+                vec![QUEEN, TEN, NINE, SEVEN, TWO],
+            ),
+            (
+                "2S 7H QD QS 10C",
+                Category::Pair,
+                vec![QUEEN, TEN, SEVEN, TWO],
+            ),
+            ("2S 7H QD QS 7C", Category::TwoPair, vec![QUEEN, SEVEN, TWO]),
+            (
+                "2S QH QD QS 7C",
+                Category::ThreeOfAKind,
+                vec![QUEEN, SEVEN, TWO],
+            ),
+            ("AS 2H 3D 4S 5C", Category::Straight, vec![FIVE]),
+            ("10S JH QD KS AC", Category::Straight, vec![ACE]),
+            (
+                "2S 7S QS 9S 10S",
+                Category::Flush,
+                vec![QUEEN, TEN, NINE, SEVEN, TWO],
+            ),
+            ("2S 2H QD QS QC", Category::FullHouse, vec![QUEEN, TWO]),
+            ("QH QD QS QC 7C", Category::FourOfAKind, vec![QUEEN, SEVEN]),
+            ("8H 9H 10H JH QH", Category::StraightFlush, vec![QUEEN]),
+            ("QH QD QS QC QC", Category::FiveOfAKind, vec![QUEEN]),
+        ];
+        for (cards, category, kickers) in hands.iter() {
+            let hand = eval(cards);
+            assert_eq!(hand.category(), *category, "{}", cards);
+            assert_eq!(hand.kickers(), &kickers[..], "{}", cards);
+        }
+        assert_eq!(eval("2S 2H QD QS QC").to_string(), "Full House: Q, 2");
+    }
+
+    #[test]
+    fn evaluate_best_of_seven() {
+        // two trips make a full house with the lower one as the pair
+        let hand = eval("2S 2H 2D QS QC QD 7C");
+        assert_eq!(
+            (hand.category(), hand.kickers()),
+            (Category::FullHouse, &[QUEEN, TWO][..])
+        );
+        // the kicker of three pairs is the third pair when it beats the single card
+        assert_eq!(
+            eval("2S 2H 9D QS QC 9C 7C").kickers(),
+            &[QUEEN, NINE, SEVEN]
+        );
+        // a flush beats the straight made with other suits
+        let hand = eval("4H 5H 6H 7S 8H 2H 9C");
+        assert_eq!(hand.category(), Category::Flush);
+        assert_eq!(hand.kickers(), &[EIGHT, SIX, FIVE, FOUR, TWO]);
+        // the straight flush isn't necessarily the highest straight
+        let hand = eval("4H 5H 6H 7H 8H 9C 10C");
+        assert_eq!(
+            (hand.category(), hand.kickers()),
+            (Category::StraightFlush, &[EIGHT][..])
+        );
+        // four of a kind with a better kicker than the three of a kind
+        assert_eq!(eval("7S 7H 7D 7C 2S 2H 2D").kickers(), &[SEVEN, TWO]);
+        assert_eq!(eval("7S 7H 7D 7C 2S 2H KD").kickers(), &[SEVEN, KING]);
+    }
+
+    #[test]
+    fn order_hands() {
+        let hands = [
+            "2S 3H 4D 5S 7C",
+            "2S 3H 4D 6S 7C",
+            "AS KH QD JS 9C",
+            "2S 2H 4D 5S 6C",
+            "2S 2H AD KS QC",
+            "3S 3H 4D 5S 6C",
+            "3S 3H 2D 2S AC",
+            "4S 4H 2D 2S 3C",
+            "2S 2H 2D 3S 4C",
+            "AS 2H 3D 4S 5C",
+            "2S 3H 4D 5S 6C",
+            "10S JH QD KS AC",
+            "2S 3S 4S 5S 7S",
+            "2S 2H 2D 3S 3C",
+            "3S 3H 3D 2S 2C",
+            "2S 2H 2D 2C 3S",
+            "AS 2S 3S 4S 5S",
+            "10S JS QS KS AS",
+            "2S 2H 2D 2C 2C",
+        ];
+        for pair in hands.windows(2) {
+            assert!(eval(pair[0]) < eval(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        // suits don't matter
+        assert_eq!(eval("2S 3H 4D 5S 7C"), eval("2H 3D 4S 5C 7H"));
+    }
+
+    #[test]
+    fn evaluate_errors() {
+        let deck: Deck = "2S 3H 4D 5S".parse().unwrap();
+        assert_eq!(
+            evaluate(deck.cards()),
+            Err("a poker hand has between 5 and 7 cards, got 4".to_string())
+        );
+        let deck: Deck = "2S 3H 4D 5S 7C 8C 9C 10C".parse().unwrap();
+        assert!(evaluate(deck.cards()).is_err());
+    }
+
+    #[test]
+    fn serialize_hand() {
+        assert_eq!(
+            serde_json::to_value(eval("2S 2H QD QS QC")).unwrap(),
+            serde_json::json!({ "category": "FullHouse", "kickers": ["QUEEN", "TWO"] })
+        );
+    }
+}
@@ -1,13 +1,15 @@
 //! # provably fair baccarat
 //!
-//! Deterministically simulates a game of baccarat. Assumes an inifinite amount of card decks.
+//! Deterministically simulates a game of baccarat. Assumes an inifinite amount of card decks,
+//! unless dealt from a shoe with `simulate_with_dealing`.
 
 /*
 use std::env;
 use std::error::Error; use std::fs;
 */
 
-use crate::card::Card;
+pub use super::Dealing;
+use crate::card::{Card, Dealer};
 pub use crate::rng::{ProvablyFairConfig, ProvablyFairRNG};
 use serde::Serialize;
 
@@ -67,23 +69,23 @@ impl SimulationResult {
         }
     }
 }
+/// Cards are printed in ASCII with `{:#}`.
 impl fmt::Display for SimulationResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fn pretty_print_steps(recipient: &BaccaratCardRecipient, steps: &Vec<Step>) -> String {
+        let ascii = f.alternate();
+        let pretty_print_steps = |recipient: &BaccaratCardRecipient, steps: &[Step]| {
             let step_str = steps
                 .iter()
-                .filter_map(|Step(r, c)| {
-                    if r == recipient {
-                        Some(c.to_string())
-                    } else {
-                        None
-                    }
+                .filter_map(|Step(r, c)| match (r == recipient, ascii) {
+                    (true, true) => Some(format!("{:#}", c)),
+                    (true, false) => Some(c.to_string()),
+                    (false, _) => None,
                 })
                 .collect::<Vec<String>>()
                 .join(" - ");
             let total = sum_cards(recipient, steps);
             format!("{} ({}): {}", recipient, total, step_str)
-        }
+        };
         let banker = pretty_print_steps(&BANKER, &self.steps);
         let player = pretty_print_steps(&PLAYER, &self.steps);
 
@@ -99,8 +101,8 @@ pub enum BaccaratCardRecipient {
 impl fmt::Display for BaccaratCardRecipient {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            BANKER => f.write_str("banker"),
-            PLAYER => f.write_str("player"),
+            BANKER => f.write_str("Banker"),
+            PLAYER => f.write_str("Player"),
         }
     }
 }
@@ -109,7 +111,7 @@ fn baccarat_add(left: u32, right: u32) -> u32 {
     (left + right) % 10
 }
 
-fn sum_cards(for_recipient: &BaccaratCardRecipient, steps: &Vec<Step>) -> u32 {
+fn sum_cards(for_recipient: &BaccaratCardRecipient, steps: &[Step]) -> u32 {
     steps
         .iter()
         .filter(|Step(recipient, _)| recipient == for_recipient)
@@ -118,11 +120,11 @@ fn sum_cards(for_recipient: &BaccaratCardRecipient, steps: &Vec<Step>) -> u32 {
         })
 }
 
-fn sum_cards_player(steps: &Vec<Step>) -> u32 {
-    sum_cards(&PLAYER, &steps)
+fn sum_cards_player(steps: &[Step]) -> u32 {
+    sum_cards(&PLAYER, steps)
 }
-fn sum_cards_banker(steps: &Vec<Step>) -> u32 {
-    sum_cards(&BANKER, &steps)
+fn sum_cards_banker(steps: &[Step]) -> u32 {
+    sum_cards(&BANKER, steps)
 }
 
 /// Simulates a game of baccarat.
@@ -138,15 +140,20 @@ fn sum_cards_banker(steps: &Vec<Step>) -> u32 {
 /// ```
 ///
 pub fn simulate(config: ProvablyFairConfig) -> SimulationResult {
-    let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config);
+    simulate_with_dealing(config, Dealing::Infinite)
+}
 
-    // keep track of drawn cards
-    let mut steps: Vec<Step> = vec![];
+/// Same as `simulate` but the cards can be dealt from a finite shoe, e.g. `Dealing::Shoe(8)`.
+pub fn simulate_with_dealing(config: ProvablyFairConfig, dealing: Dealing) -> SimulationResult {
+    let mut dealer = Dealer::new(config, dealing);
 
-    steps.push(Step(PLAYER, Card::random(&mut rng)));
-    steps.push(Step(PLAYER, Card::random(&mut rng)));
-    steps.push(Step(BANKER, Card::random(&mut rng)));
-    steps.push(Step(BANKER, Card::random(&mut rng)));
+    // keep track of drawn cards
+    let mut steps: Vec<Step> = vec![
+        Step(PLAYER, dealer.deal()),
+        Step(PLAYER, dealer.deal()),
+        Step(BANKER, dealer.deal()),
+        Step(BANKER, dealer.deal()),
+    ];
 
     // If either The player or banker or both achieve a total of 8 or 9
     // at this stage, the coup is finished and the result is announced:
@@ -164,55 +171,116 @@ pub fn simulate(config: ProvablyFairConfig) -> SimulationResult {
         // If the Player stands pat (or draws no new cards), the Banker draws with
         // a hand total of 0-5 and stays pat with a hand total of 6 or 7.
         if sum_cards_banker(&steps) <= 5 {
-            steps.push(Step(BANKER, Card::random(&mut rng)));
+            steps.push(Step(BANKER, dealer.deal()));
         }
         return SimulationResult::from_steps(steps);
     }
 
     // If the player has an initial total of 0–5, he draws a third card.
-    let player_third_card = Card::random(&mut rng);
+    let player_third_card = dealer.deal();
     steps.push(Step(PLAYER, player_third_card));
 
-    fn banker_should_draw_third_card(banker_total: u32, player_third_card: Card) -> bool {
-        let rank = player_third_card.to_baccarat_value();
-
-        match banker_total {
-            0 | 1 | 2 => true,
-            3 => rank != 8,
-            4 => match rank {
-                2 | 3 | 4 | 5 | 6 | 7 => true,
-                _ => false,
-            },
-            5 => match rank {
-                4 | 5 | 6 | 7 => true,
-                _ => false,
-            },
-            6 => match rank {
-                6 | 7 => true,
-                _ => false,
-            },
-            7 => false,
-            _ => {
-                panic!(
-                    "got an impossible value \"{}\" for banker total (>7), something with library!",
-                    banker_total
-                );
-            }
+    if banker_should_draw_third_card(
+        sum_cards_banker(&steps),
+        player_third_card.to_baccarat_value(),
+    ) {
+        steps.push(Step(BANKER, dealer.deal()));
+    }
+
+    SimulationResult::from_steps(steps)
+}
+
+fn banker_should_draw_third_card(banker_total: u32, player_third_card_value: u8) -> bool {
+    let rank = player_third_card_value;
+
+    match banker_total {
+        0..=2 => true,
+        3 => rank != 8,
+        4 => matches!(rank, 2..=7),
+        5 => matches!(rank, 4..=7),
+        6 => matches!(rank, 6 | 7),
+        7 => false,
+        _ => {
+            panic!(
+                "got an impossible value \"{}\" for banker total (>7), something with library!",
+                banker_total
+            );
         }
     }
+}
+
+/// Exact probability of each outcome (banker, player and tie, in that order), assuming an
+/// infinite amount of card decks.
+pub fn outcome_probabilities() -> [(Outcome, f64); 3] {
+    // 10, J, Q and K are all worth 0
+    let card_values: Vec<(u32, f64)> = (0..10)
+        .map(|v| (v, if v == 0 { 4. / 13. } else { 1. / 13. }))
+        .collect();
 
-    if banker_should_draw_third_card(sum_cards_banker(&steps), player_third_card) {
-        steps.push(Step(BANKER, Card::random(&mut rng)));
+    let mut totals = [0.; 3];
+    let mut record = |player: u32, banker: u32, probability: f64| {
+        let idx = match player.cmp(&banker) {
+            Ordering::Less => 0,
+            Ordering::Greater => 1,
+            Ordering::Equal => 2,
+        };
+        totals[idx] += probability;
+    };
+
+    for &(p1, prob_p1) in &card_values {
+        for &(p2, prob_p2) in &card_values {
+            for &(b1, prob_b1) in &card_values {
+                for &(b2, prob_b2) in &card_values {
+                    let probability = prob_p1 * prob_p2 * prob_b1 * prob_b2;
+                    let player = baccarat_add(p1, p2);
+                    let banker = baccarat_add(b1, b2);
+
+                    // natural win
+                    if player >= 8 || banker >= 8 {
+                        record(player, banker, probability);
+                        continue;
+                    }
+
+                    // player stands pat
+                    if player > 5 {
+                        if banker <= 5 {
+                            for &(b3, prob_b3) in &card_values {
+                                record(player, baccarat_add(banker, b3), probability * prob_b3);
+                            }
+                        } else {
+                            record(player, banker, probability);
+                        }
+                        continue;
+                    }
+
+                    for &(p3, prob_p3) in &card_values {
+                        let player = baccarat_add(player, p3);
+                        let probability = probability * prob_p3;
+                        if banker_should_draw_third_card(banker, p3 as u8) {
+                            for &(b3, prob_b3) in &card_values {
+                                record(player, baccarat_add(banker, b3), probability * prob_b3);
+                            }
+                        } else {
+                            record(player, banker, probability);
+                        }
+                    }
+                }
+            }
+        }
     }
 
-    SimulationResult::from_steps(steps)
+    [
+        (Outcome::Banker, totals[0]),
+        (Outcome::Player, totals[1]),
+        (Outcome::Tie, totals[2]),
+    ]
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    fn pretty_print_steps(steps: &Vec<Step>) -> Vec<String> {
+    fn pretty_print_steps(steps: &[Step]) -> Vec<String> {
         steps
             .iter()
             .map(|Step(recipient, card)| format!("{}: {}", recipient, card))
@@ -238,6 +306,18 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_outcome_probabilities() {
+        let probabilities = outcome_probabilities();
+        let total: f64 = probabilities.iter().map(|(_, p)| p).sum();
+        assert!((total - 1.).abs() < 1e-9);
+        // well known infinite deck probabilities
+        assert_eq!(probabilities[0].0, Outcome::Banker);
+        assert!((probabilities[0].1 - 0.4584).abs() < 1e-4);
+        assert!((probabilities[1].1 - 0.4461).abs() < 1e-4);
+        assert!((probabilities[2].1 - 0.0954).abs() < 1e-4);
+    }
+
     #[test]
     fn simulate_four_cards_drawn() {
         let config = ProvablyFairConfig::new("some client seed", "some server seed", 1);
@@ -7,7 +7,8 @@ use std::env;
 use std::error::Error; use std::fs;
 */
 
-use crate::card::Deck;
+pub use super::Dealing;
+use crate::card::{Dealer, Deck};
 pub use crate::rng::{ProvablyFairConfig, ProvablyFairRNG};
 use serde::Serialize;
 
@@ -20,21 +21,35 @@ pub struct SimulationResult {
     deck: Deck,
 }
 
+/// Cards are printed in ASCII with `{:#}`.
 impl fmt::Display for SimulationResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Dealer: {}\nPlayer: {}\nDeck: {}",
-            self.dealer, self.player, self.deck
-        )
+        if f.alternate() {
+            write!(
+                f,
+                "Dealer: {:#}\nPlayer: {:#}\nDeck: {:#}",
+                self.dealer, self.player, self.deck
+            )
+        } else {
+            write!(
+                f,
+                "Dealer: {}\nPlayer: {}\nDeck: {}",
+                self.dealer, self.player, self.deck
+            )
+        }
     }
 }
 
 pub fn simulate(config: ProvablyFairConfig) -> SimulationResult {
-    let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config);
-    let player = Deck::from_rng(&mut rng, 2);
-    let dealer = Deck::from_rng(&mut rng, 2);
-    let deck = Deck::from_rng(&mut rng, 52 - 4);
+    simulate_with_dealing(config, Dealing::Infinite)
+}
+
+/// Same as `simulate` but the cards can be dealt from a finite shoe, e.g. `Dealing::Shoe(6)`.
+pub fn simulate_with_dealing(config: ProvablyFairConfig, dealing: Dealing) -> SimulationResult {
+    let mut cards = Dealer::new(config, dealing);
+    let player = cards.deal_deck(2);
+    let dealer = cards.deal_deck(2);
+    let deck = cards.deal_deck(52 - 4);
     SimulationResult {
         dealer,
         player,
@@ -31,13 +31,18 @@ all set in stone.
 Excited to show you all Crash very soon!
 
 */
+pub mod analysis;
+
 use hex;
 use hmac::{Hmac, Mac};
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 use sha2::digest::generic_array::typenum::*;
 use sha2::digest::generic_array::GenericArray;
 use sha2::{Digest, Sha256};
 use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Hash {
@@ -51,6 +56,12 @@ impl fmt::Display for Hash {
     }
 }
 
+impl Serialize for Hash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl Hash {
     pub fn new(value: GenericArray<u8, U32>) -> Hash {
         Hash { value }
@@ -62,16 +73,29 @@ impl Hash {
         let v = hex::decode(s).unwrap();
         Hash::new(GenericArray::clone_from_slice(&v[..]))
     }
-    fn to_hex(&self) -> String {
+    fn to_hex(self) -> String {
         self.to_string()
     }
 }
 
+impl std::str::FromStr for Hash {
+    type Err = String;
+
+    /// Parses a hash given by the user, unlike `from_hex` which panics if it is invalid.
+    fn from_str(s: &str) -> Result<Hash, String> {
+        match hex::decode(s) {
+            Ok(v) if v.len() == 32 => Ok(Hash::new(GenericArray::clone_from_slice(&v[..]))),
+            _ => Err("game hash must be 64 characters hexadecimal".to_string()),
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct Config {
     hash_chain_tip: Hash,
     block_hash: Hash,
     max_chain_length: usize,
+    house_edge: f64,
 }
 
 impl Config {
@@ -80,8 +104,26 @@ impl Config {
             hash_chain_tip,
             block_hash,
             max_chain_length,
+            house_edge: 0.01,
         }
     }
+    /// Overrides the default house edge of 1% (e.g. `0.03` for 3%).
+    pub fn with_house_edge(self, house_edge: f64) -> Config {
+        validate_house_edge(house_edge).unwrap();
+        Config { house_edge, ..self }
+    }
+    pub fn hash_chain_tip(&self) -> Hash {
+        self.hash_chain_tip
+    }
+    pub fn block_hash(&self) -> Hash {
+        self.block_hash
+    }
+    pub fn max_chain_length(&self) -> usize {
+        self.max_chain_length
+    }
+    pub fn house_edge(&self) -> f64 {
+        self.house_edge
+    }
     pub fn for_stake() -> Config {
         // https://bitcointalk.org/index.php?topic=5162888.msg54134231#msg54134231
         // e.g. game:
@@ -93,16 +135,22 @@ impl Config {
             Hash::from_hex("0000000000000000001b34dc6a1e86083f95500b096231436e9b25cbdd0075c4");
         let max_chain_length = 10_000_000;
 
-        Config {
-            hash_chain_tip,
-            block_hash,
-            max_chain_length,
-        }
+        Config::new(hash_chain_tip, block_hash, max_chain_length)
     }
 }
 
+pub fn validate_house_edge(house_edge: f64) -> Result<(), String> {
+    if (0. ..1.).contains(&house_edge) {
+        Ok(())
+    } else {
+        Err("house edge must be at least 0 and less than 1".to_string())
+    }
+}
+
+/// Walks the hash chain backwards in time: the hash following a game hash is the hash of the game
+/// that was played right before it.
 #[derive(Copy, Clone)]
-struct HashChain {
+pub struct HashChain {
     hash: Hash,
 }
 
@@ -111,6 +159,14 @@ impl HashChain {
         HashChain { hash: initial_hash }
     }
 
+    /// Turns the chain into an iterator of `(game_hash, crash_point)` pairs.
+    pub fn games(self, config: Config) -> Games {
+        Games {
+            chain: self,
+            config,
+        }
+    }
+
     fn compute_next_hash(&self) -> Hash {
         Hash::digest(&self.hash.to_hex())
     }
@@ -126,13 +182,28 @@ impl std::iter::Iterator for HashChain {
     }
 }
 
+/// Infinite iterator over the games of a hash chain, starting with the chain's initial hash and
+/// going back in time. See `HashChain::games` and `history`.
+pub struct Games {
+    chain: HashChain,
+    config: Config,
+}
+
+impl std::iter::Iterator for Games {
+    type Item = (Hash, f64);
+    fn next(&mut self) -> Option<Self::Item> {
+        let game_hash = self.chain.next().unwrap();
+        Some((game_hash, simulate(self.config, game_hash).crash_point))
+    }
+}
+
 type HmacSha256 = Hmac<Sha256>;
 
 use std::convert::TryInto;
 
 #[derive(Serialize)]
 pub struct Outcome {
-    crash_point: f64,
+    pub crash_point: f64,
 }
 
 impl fmt::Display for Outcome {
@@ -154,21 +225,200 @@ pub fn simulate(config: Config, game_hash: Hash) -> Outcome {
 
     let n = n as f64;
 
-    let crash_point = 1_f64.max((2_f64.powf(32.) / (n + 1.)) * (1. - 0.01));
+    let crash_point = 1_f64.max((2_f64.powf(32.) / (n + 1.)) * (1. - config.house_edge));
 
     Outcome { crash_point }
 }
 
+/// Reconstructs the history of games leading up to (and including) `game_hash`.
+///
+/// # Example
+///
+/// ```
+/// use fair::games::crash;
+///
+/// let config = crash::Config::for_stake();
+/// let game_hash =
+///     crash::Hash::from_hex("5844bf329a6334074778ab8a5f0960e24f9eec43f83bbd98ac0a9f8bcd87184e");
+/// // the game itself followed by the 10 games that were played before it
+/// for (hash, crash_point) in crash::history(config, game_hash).take(11) {
+///     println!("{} {}", hash, crash_point);
+/// }
+/// ```
+///
+pub fn history(config: Config, game_hash: Hash) -> Games {
+    HashChain::new(game_hash).games(config)
+}
+
 // verify that the hash is really part of the hash chain
 pub fn verify_hash(config: Config, game_hash: Hash) -> bool {
-    let mut hash_chain = HashChain::new(game_hash);
-    for _ in 0..config.max_chain_length {
-        let h = hash_chain.next().unwrap();
-        if h == config.hash_chain_tip {
-            return true;
+    verify_hash_with_progress(config, game_hash, |_| true).unwrap()
+}
+
+/// How many hashes are walked between two progress reports.
+pub const PROGRESS_INTERVAL: usize = 100_000;
+
+/// Number of hashes walked so far out of the maximum number of hashes that may have to be walked.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    pub walked: usize,
+    pub total: usize,
+}
+
+/// Same as `verify_hash` but calls `on_progress` every `PROGRESS_INTERVAL` hashes. Verification
+/// stops and `None` is returned as soon as `on_progress` returns `false`.
+///
+/// # Example
+///
+/// ```
+/// use fair::games::crash;
+///
+/// let config = crash::Config::for_stake();
+/// let game_hash =
+///     crash::Hash::from_hex("5844bf329a6334074778ab8a5f0960e24f9eec43f83bbd98ac0a9f8bcd87184e");
+/// let res = crash::verify_hash_with_progress(config, game_hash, |progress| {
+///     println!("{}/{}", progress.walked, progress.total);
+///     // give up on the first progress report
+///     false
+/// });
+/// assert_eq!(res, None);
+/// ```
+///
+pub fn verify_hash_with_progress<F>(
+    config: Config,
+    game_hash: Hash,
+    mut on_progress: F,
+) -> Option<bool>
+where
+    F: FnMut(Progress) -> bool,
+{
+    let mut verifier = Verifier::new(config, game_hash);
+    loop {
+        if let Some(res) = verifier.step(PROGRESS_INTERVAL) {
+            return Some(res);
+        }
+        if !on_progress(verifier.progress()) {
+            return None;
+        }
+    }
+}
+
+/// Resumable version of `verify_hash`, for callers that can't block until the whole chain is
+/// walked (e.g. a browser's main thread): each call to `step` walks a bounded number of hashes.
+///
+/// # Example
+///
+/// ```
+/// use fair::games::crash;
+///
+/// let config = crash::Config::for_stake();
+/// let game_hash =
+///     crash::Hash::from_hex("5844bf329a6334074778ab8a5f0960e24f9eec43f83bbd98ac0a9f8bcd87184e");
+/// let mut verifier = crash::Verifier::new(config, game_hash);
+/// let res = loop {
+///     if let Some(res) = verifier.step(1_000_000) {
+///         break res;
+///     }
+///     let progress = verifier.progress();
+///     println!("{}/{}", progress.walked, progress.total);
+/// };
+/// assert!(res);
+/// ```
+///
+pub struct Verifier {
+    config: Config,
+    hash_chain: HashChain,
+    walked: usize,
+    result: Option<bool>,
+}
+
+impl Verifier {
+    pub fn new(config: Config, game_hash: Hash) -> Verifier {
+        Verifier {
+            config,
+            hash_chain: HashChain::new(game_hash),
+            walked: 0,
+            result: None,
         }
     }
-    return false;
+
+    /// Walks at most `steps` more hashes and returns the result once it is known.
+    pub fn step(&mut self, steps: usize) -> Option<bool> {
+        if self.result.is_some() {
+            return self.result;
+        }
+        let end = self.config.max_chain_length.min(self.walked + steps);
+        while self.walked < end {
+            self.walked += 1;
+            if self.hash_chain.next().unwrap() == self.config.hash_chain_tip {
+                self.result = Some(true);
+                return self.result;
+            }
+        }
+        if self.walked == self.config.max_chain_length {
+            self.result = Some(false);
+        }
+        self.result
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            walked: self.walked,
+            total: self.config.max_chain_length,
+        }
+    }
+
+    /// `None` until `step` has walked far enough to know whether the hash is in the chain.
+    pub fn result(&self) -> Option<bool> {
+        self.result
+    }
+}
+
+/// Verifies many game hashes in parallel, using one thread per available core.
+///
+/// `on_progress` is called from the worker threads with the number of hashes walked across all
+/// game hashes. Returning `false` cancels the verification of every game hash that is not done
+/// yet, in which case their result is `None`.
+pub fn verify_hashes<F>(config: Config, game_hashes: &[Hash], on_progress: F) -> Vec<Option<bool>>
+where
+    F: Fn(Progress) -> bool + Sync,
+{
+    let total = config.max_chain_length * game_hashes.len();
+    let walked = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
+    let next_index = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; game_hashes.len()]);
+
+    let threads = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(game_hashes.len());
+
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let idx = next_index.fetch_add(1, Ordering::SeqCst);
+                if idx >= game_hashes.len() || cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
+                let res = verify_hash_with_progress(config, game_hashes[idx], |_| {
+                    let walked = walked.fetch_add(PROGRESS_INTERVAL, Ordering::SeqCst);
+                    let progress = Progress {
+                        walked: walked + PROGRESS_INTERVAL,
+                        total,
+                    };
+                    if cancelled.load(Ordering::SeqCst) || !on_progress(progress) {
+                        cancelled.store(true, Ordering::SeqCst);
+                        return false;
+                    }
+                    true
+                });
+                results.lock().unwrap()[idx] = res;
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
 }
 
 #[cfg(test)]
@@ -196,6 +446,13 @@ mod test {
         );
     }
     #[test]
+    fn test_parse_hash() {
+        let hash = Hash::digest("testing");
+        assert_eq!(hash.to_hex().parse(), Ok(hash));
+        assert!("cf80cd".parse::<Hash>().is_err());
+        assert!("zz".repeat(32).parse::<Hash>().is_err());
+    }
+    #[test]
     fn test_crash_simulate() {
         let hash_chain: Vec<_> = HashChain::new(Hash::digest("testing")).take(10).collect();
         let hash_chain_tip = *hash_chain.last().unwrap();
@@ -226,6 +483,126 @@ mod test {
         assert_eq!(simulate(config, game_hash).crash_point, 1.2897005203687084);
     }
 
+    #[test]
+    fn test_crash_history() {
+        let hash_chain: Vec<_> = HashChain::new(Hash::digest("testing")).take(10).collect();
+        let hash_chain_tip = *hash_chain.last().unwrap();
+        let block_hash =
+            Hash::from_hex("0000000000000000001b34dc6a1e86083f95500b096231436e9b25cbdd0075c4");
+        let config = Config::new(hash_chain_tip, block_hash, hash_chain.len());
+
+        let games: Vec<_> = history(config, hash_chain[0]).take(10).collect();
+        assert_eq!(games.len(), 10);
+        for (i, (game_hash, crash_point)) in games.iter().enumerate() {
+            assert_eq!(*game_hash, hash_chain[i]);
+            assert_eq!(*crash_point, simulate(config, hash_chain[i]).crash_point);
+        }
+        assert_eq!(games[2].1, 1.5992214910117746);
+    }
+
+    #[test]
+    fn test_crash_verify_with_progress() {
+        let hash_chain: Vec<_> = HashChain::new(Hash::digest("testing"))
+            .take(PROGRESS_INTERVAL + 10)
+            .collect();
+        let hash_chain_tip = *hash_chain.last().unwrap();
+        let block_hash =
+            Hash::from_hex("0000000000000000001b34dc6a1e86083f95500b096231436e9b25cbdd0075c4");
+        let config = Config::new(hash_chain_tip, block_hash, hash_chain.len());
+
+        let mut reports = vec![];
+        let res = verify_hash_with_progress(config, hash_chain[0], |p| {
+            reports.push(p);
+            true
+        });
+        assert_eq!(res, Some(true));
+        assert_eq!(
+            reports,
+            vec![Progress {
+                walked: PROGRESS_INTERVAL,
+                total: hash_chain.len()
+            }]
+        );
+
+        // cancelled on first progress report
+        assert_eq!(
+            verify_hash_with_progress(config, hash_chain[0], |_| false),
+            None
+        );
+        // finishes before the first progress report
+        assert_eq!(
+            verify_hash_with_progress(config, hash_chain[hash_chain.len() - 5], |_| false),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_crash_verify_hashes() {
+        let hash_chain: Vec<_> = HashChain::new(Hash::digest("testing")).take(1000).collect();
+        let hash_chain_tip = *hash_chain.last().unwrap();
+        let block_hash =
+            Hash::from_hex("0000000000000000001b34dc6a1e86083f95500b096231436e9b25cbdd0075c4");
+        let config = Config::new(hash_chain_tip, block_hash, hash_chain.len());
+        let bad_game_hash =
+            Hash::from_hex("deadbeefe7c270724bd4851c020d489257fa79a70e694a9b5099375464348698");
+
+        let game_hashes = vec![
+            hash_chain[0],
+            bad_game_hash,
+            hash_chain[500],
+            hash_chain[999],
+        ];
+        assert_eq!(
+            verify_hashes(config, &game_hashes, |_| true),
+            vec![Some(true), Some(false), Some(true), Some(true)]
+        );
+        assert_eq!(verify_hashes(config, &[], |_| true), vec![]);
+    }
+
+    #[test]
+    fn test_crash_verifier() {
+        let hash_chain: Vec<_> = HashChain::new(Hash::digest("testing")).take(100).collect();
+        let hash_chain_tip = *hash_chain.last().unwrap();
+        let block_hash =
+            Hash::from_hex("0000000000000000001b34dc6a1e86083f95500b096231436e9b25cbdd0075c4");
+        let config = Config::new(hash_chain_tip, block_hash, hash_chain.len());
+
+        let mut verifier = Verifier::new(config, hash_chain[0]);
+        assert_eq!(verifier.step(60), None);
+        assert_eq!(
+            verifier.progress(),
+            Progress {
+                walked: 60,
+                total: 100
+            }
+        );
+        assert_eq!(verifier.step(60), Some(true));
+        assert_eq!(verifier.progress().walked, 100);
+        assert_eq!(verifier.result(), Some(true));
+
+        let bad_game_hash =
+            Hash::from_hex("deadbeefe7c270724bd4851c020d489257fa79a70e694a9b5099375464348698");
+        let mut verifier = Verifier::new(config, bad_game_hash);
+        assert_eq!(verifier.step(99), None);
+        assert_eq!(verifier.step(1), Some(false));
+        assert_eq!(verifier.step(1), Some(false));
+        assert_eq!(verifier.progress().walked, 100);
+    }
+
+    #[test]
+    fn test_crash_house_edge() {
+        let hash_chain_tip =
+            Hash::from_hex("0000000000000000001b34dc6a1e86083f95500b096231436e9b25cbdd0075c4");
+        let block_hash = hash_chain_tip;
+        let game_hash =
+            Hash::from_hex("deadbeefe7c270724bd4851c020d489257fa79a70e694a9b5099375464348698");
+        let config = Config::new(hash_chain_tip, block_hash, 0);
+        assert_eq!(config.house_edge(), 0.01);
+        let config = config.with_house_edge(0.03);
+        let crash_point = simulate(config, game_hash).crash_point;
+        assert!((crash_point - 1.2897005203687084 / 0.99 * 0.97).abs() < 1e-12);
+    }
+
     #[test]
     #[ignore] // too slow
     fn test_crash_verify() {
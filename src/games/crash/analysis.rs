@@ -0,0 +1,223 @@
+//! # crash history analysis
+//!
+//! Statistics over a sequence of crash points (e.g. reconstructed with `crash::history`) and a
+//! goodness-of-fit test against the theoretical distribution. With a house edge `e`, the
+//! probability that a game crashes at or above `x` is `(1 - e) / x` for any `x >= 1`.
+
+use crate::stats::{self, ChiSquare};
+use serde::Serialize;
+use std::fmt;
+
+/// Thresholds used by the CLI when none are given. Crash points are displayed rounded down to
+/// two decimals, so games below 1.01 are the ones that busted at 1.00x.
+pub const DEFAULT_THRESHOLDS: [f64; 3] = [1.01, 2., 10.];
+
+/// Probability that a game crashes strictly below `x`.
+pub fn theoretical_cdf(x: f64, house_edge: f64) -> f64 {
+    if x <= 1. {
+        0.
+    } else {
+        1. - (1. - house_edge) / x
+    }
+}
+
+/// Fraction of games that crashed strictly below `threshold`.
+pub fn empirical_cdf(crash_points: &[f64], threshold: f64) -> f64 {
+    if crash_points.is_empty() {
+        return 0.;
+    }
+    let below = crash_points.iter().filter(|&&c| c < threshold).count();
+    below as f64 / crash_points.len() as f64
+}
+
+/// Longest run of consecutive games that crashed strictly below `threshold`.
+pub fn longest_streak_below(crash_points: &[f64], threshold: f64) -> usize {
+    let (longest, _) = crash_points
+        .iter()
+        .fold((0, 0), |(longest, current), &crash_point| {
+            if crash_point < threshold {
+                (longest.max(current + 1), current + 1)
+            } else {
+                (longest, 0)
+            }
+        });
+    longest
+}
+
+/// Chi-square test of the crash points against the theoretical distribution. Games are put in
+/// bins of (roughly) equal theoretical probability, with at least 5 expected games per bin.
+pub fn goodness_of_fit(crash_points: &[f64], house_edge: f64) -> ChiSquare {
+    let bin_count = (crash_points.len() / 5).clamp(2, 20);
+    // bin edges are the quantiles of the theoretical distribution, the atom at 1 (probability
+    // `house_edge`) swallows the first edges when the house edge is large
+    let edges: Vec<f64> = (1..bin_count)
+        .map(|i| {
+            let p = i as f64 / bin_count as f64;
+            (1. - house_edge) / (1. - p)
+        })
+        .filter(|&edge| edge > 1.)
+        .collect();
+
+    let mut probabilities = vec![];
+    let mut observed = vec![];
+    let mut lower = 1.;
+    for &upper in edges.iter().chain(std::iter::once(&f64::INFINITY)) {
+        let p = if upper.is_infinite() {
+            1. - theoretical_cdf(lower, house_edge)
+        } else {
+            theoretical_cdf(upper, house_edge) - theoretical_cdf(lower, house_edge)
+        };
+        let count = crash_points
+            .iter()
+            .filter(|&&c| c >= lower && c < upper)
+            .count();
+        probabilities.push(p);
+        observed.push(count as u64);
+        lower = upper;
+    }
+
+    stats::chi_square(&observed, &probabilities)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdStats {
+    pub threshold: f64,
+    pub empirical: f64,
+    pub theoretical: f64,
+    pub longest_streak_below: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub games: usize,
+    pub house_edge: f64,
+    pub thresholds: Vec<ThresholdStats>,
+    pub goodness_of_fit: ChiSquare,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Games: {} (house edge: {}%)\n",
+            self.games,
+            self.house_edge * 100.
+        )?;
+        writeln!(f, "Below\tEmpirical\tTheoretical\tLongest streak below")?;
+        for t in &self.thresholds {
+            writeln!(
+                f,
+                "{:.2}x\t{:.4}\t\t{:.4}\t\t{}",
+                t.threshold, t.empirical, t.theoretical, t.longest_streak_below
+            )?;
+        }
+        write!(f, "\nGoodness of fit: {}", self.goodness_of_fit)
+    }
+}
+
+impl Report {
+    /// One line per threshold, with a header, e.g. to plot the distribution.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "threshold,empirical,theoretical,longest_streak_below\n".to_string();
+        for t in &self.thresholds {
+            csv += &format!(
+                "{},{},{},{}\n",
+                t.threshold, t.empirical, t.theoretical, t.longest_streak_below
+            );
+        }
+        csv
+    }
+}
+
+/// Analyzes a sequence of crash points.
+///
+/// # Example
+///
+/// ```
+/// use fair::games::crash;
+///
+/// let config = crash::Config::for_stake();
+/// let game_hash =
+///     crash::Hash::from_hex("5844bf329a6334074778ab8a5f0960e24f9eec43f83bbd98ac0a9f8bcd87184e");
+/// let crash_points: Vec<f64> = crash::history(config, game_hash)
+///     .take(1000)
+///     .map(|(_, crash_point)| crash_point)
+///     .collect();
+/// let report = crash::analysis::analyze(&crash_points, config.house_edge(), &[1.01, 2.]);
+/// println!("{}", report);
+/// ```
+///
+pub fn analyze(crash_points: &[f64], house_edge: f64, thresholds: &[f64]) -> Report {
+    let thresholds = thresholds
+        .iter()
+        .map(|&threshold| ThresholdStats {
+            threshold,
+            empirical: empirical_cdf(crash_points, threshold),
+            theoretical: theoretical_cdf(threshold, house_edge),
+            longest_streak_below: longest_streak_below(crash_points, threshold),
+        })
+        .collect();
+    Report {
+        games: crash_points.len(),
+        house_edge,
+        thresholds,
+        goodness_of_fit: goodness_of_fit(crash_points, house_edge),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::games::crash::{history, Config, Hash};
+
+    #[test]
+    fn test_longest_streak_below() {
+        let crash_points = [1., 1.5, 3., 1.2, 1.9, 1.01, 2., 1.];
+        assert_eq!(longest_streak_below(&crash_points, 2.), 3);
+        assert_eq!(longest_streak_below(&crash_points, 1.01), 1);
+        assert_eq!(longest_streak_below(&crash_points, 1.), 0);
+        assert_eq!(longest_streak_below(&[], 2.), 0);
+    }
+
+    #[test]
+    fn test_cdf() {
+        assert_eq!(theoretical_cdf(1., 0.01), 0.);
+        assert_eq!(theoretical_cdf(2., 0.01), 0.505);
+        let crash_points = [1., 1.5, 3., 1.2];
+        assert_eq!(empirical_cdf(&crash_points, 2.), 0.75);
+        assert_eq!(empirical_cdf(&crash_points, 1.), 0.);
+    }
+
+    #[test]
+    fn test_analyze_history() {
+        let block_hash =
+            Hash::from_hex("0000000000000000001b34dc6a1e86083f95500b096231436e9b25cbdd0075c4");
+        let config = Config::new(block_hash, block_hash, 0);
+        let game_hash =
+            Hash::from_hex("deadbeefe7c270724bd4851c020d489257fa79a70e694a9b5099375464348698");
+        let crash_points: Vec<_> = history(config, game_hash)
+            .take(2000)
+            .map(|(_, crash_point)| crash_point)
+            .collect();
+
+        let report = analyze(&crash_points, config.house_edge(), &DEFAULT_THRESHOLDS);
+        assert_eq!(report.games, 2000);
+        assert_eq!(report.goodness_of_fit.degrees_of_freedom, 19);
+        assert!(report.goodness_of_fit.p_value > 0.01);
+        let below_2 = &report.thresholds[1];
+        assert!((below_2.empirical - below_2.theoretical).abs() < 0.05);
+
+        let csv = report.to_csv();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "threshold,empirical,theoretical,longest_streak_below"
+        );
+        assert_eq!(lines.len(), 1 + DEFAULT_THRESHOLDS.len());
+        assert!(lines[2].starts_with(&format!("2,{},0.505,", below_2.empirical)));
+
+        // same games can't come from a 50% house edge
+        let report = analyze(&crash_points, 0.5, &DEFAULT_THRESHOLDS);
+        assert!(report.goodness_of_fit.p_value < 1e-6);
+    }
+}
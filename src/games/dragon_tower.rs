@@ -0,0 +1,307 @@
+//! # provably fair Dragon Tower
+//!
+
+/*
+
+Dragon Tower is a tower of 9 rows, each hiding eggs among a number of tiles that depends on the
+difficulty. The eggs of each row are placed like the mines of Mines: each float is multiplied by
+the number of tiles still remaining in the row, which is decremented after every egg. The
+fisher-yates shuffle implementation is utilised to prevent duplicate eggs in a row. Rows are
+generated from the bottom to the top of the tower with consecutive floats.
+
+  // difficulty: eggs per row / tiles per row
+  easy: 3 / 4, medium: 2 / 3, hard: 1 / 2, expert: 1 / 3, master: 1 / 4
+
+The player climbs by picking a tile on each row and can cash out after any row. Picking a tile
+without an egg ends the game.
+*/
+
+pub use crate::rng::{ProvablyFairConfig, ProvablyFairRNG};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of rows of the tower.
+pub const ROWS: usize = 9;
+
+/// House edge applied to the odds of a climb, see `Difficulty::multiplier`.
+pub const HOUSE_EDGE: f64 = 0.02;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Master,
+}
+
+impl Difficulty {
+    /// Every difficulty, from easiest to hardest.
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+        Difficulty::Master,
+    ];
+    /// Names accepted by `from_str`, in the same order as `ALL`.
+    pub const NAMES: [&'static str; 5] = ["easy", "medium", "hard", "expert", "master"];
+
+    /// Number of tiles of each row.
+    pub fn tiles(self) -> u8 {
+        match self {
+            Difficulty::Easy => 4,
+            Difficulty::Medium => 3,
+            Difficulty::Hard => 2,
+            Difficulty::Expert => 3,
+            Difficulty::Master => 4,
+        }
+    }
+
+    /// Number of eggs of each row.
+    pub fn eggs(self) -> u8 {
+        match self {
+            Difficulty::Easy => 3,
+            Difficulty::Medium => 2,
+            Difficulty::Hard | Difficulty::Expert | Difficulty::Master => 1,
+        }
+    }
+
+    /// Multiplier when cashing out after climbing `rows` rows (1 to 9): the inverse of the
+    /// probability of finding an egg on every row, minus the house edge, rounded to 2 decimals.
+    pub fn multiplier(self, rows: usize) -> f64 {
+        assert!((1..=ROWS).contains(&rows));
+        let odds = (self.tiles() as f64 / self.eggs() as f64).powi(rows as i32);
+        ((1. - HOUSE_EDGE) * odds * 100.).round() / 100.
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    /// Parses a difficulty, ignoring case.
+    fn from_str(s: &str) -> Result<Difficulty, String> {
+        Difficulty::NAMES
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(s))
+            .map(|idx| Difficulty::ALL[idx])
+            .ok_or_else(|| {
+                "difficulty must be one of easy, medium, hard, expert or master".to_string()
+            })
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let idx = Difficulty::ALL.iter().position(|d| d == self).unwrap();
+        write!(f, "{}", Difficulty::NAMES[idx])
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulationResult {
+    pub difficulty: Difficulty,
+    /// tiles of the eggs of each row from the bottom, in the order they were drawn
+    pub rows: Vec<Vec<u8>>,
+}
+
+impl SimulationResult {
+    /// Whether `tile` of `row` (both starting at 0, rows from the bottom) hides an egg.
+    pub fn is_egg(&self, row: usize, tile: u8) -> bool {
+        self.rows[row].contains(&tile)
+    }
+
+    /// Resolves a climb where the player picks `picks[0]` on the bottom row, `picks[1]` on the
+    /// next one and so on, then cashes out unless a pick had no egg.
+    pub fn climb(&self, picks: &[u8]) -> Result<Climb, String> {
+        validate_picks(self.difficulty, picks)?;
+        let rows = picks
+            .iter()
+            .enumerate()
+            .take_while(|&(row, &tile)| self.is_egg(row, tile))
+            .count();
+        let busted = rows < picks.len();
+        Ok(Climb {
+            picks: picks.to_vec(),
+            rows,
+            busted,
+            multiplier: if busted {
+                0.
+            } else {
+                self.difficulty.multiplier(rows)
+            },
+        })
+    }
+}
+
+impl fmt::Display for SimulationResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tower = String::new();
+        // the top of the tower is printed first
+        for (row, eggs) in self.rows.iter().enumerate().rev() {
+            tower = format!("{}\n{}\t", tower, row + 1);
+            for tile in 0..self.difficulty.tiles() {
+                let icon = if eggs.contains(&tile) { "🥚" } else { "💀" };
+                tower = format!("{}{}\t ", tower, icon);
+            }
+        }
+
+        write!(
+            f,
+            "Difficulty: {}\nEggs: {:?}\n{}",
+            self.difficulty, self.rows, tower
+        )
+    }
+}
+
+/// Outcome of a player's climb, see `SimulationResult::climb`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Climb {
+    pub picks: Vec<u8>,
+    /// rows climbed before cashing out or picking a tile without an egg
+    pub rows: usize,
+    pub busted: bool,
+    /// 0 when busted
+    pub multiplier: f64,
+}
+
+impl fmt::Display for Climb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.busted {
+            write!(f, "No egg on row {}, payout: 0x", self.rows + 1)
+        } else {
+            write!(
+                f,
+                "Cashed out after row {}, payout: {}x",
+                self.rows, self.multiplier
+            )
+        }
+    }
+}
+
+/// Checks there is a pick for 1 to 9 rows, each between 0 and the number of tiles excluded.
+pub fn validate_picks(difficulty: Difficulty, picks: &[u8]) -> Result<(), String> {
+    if picks.is_empty() || picks.len() > ROWS {
+        return Err(format!("picks must cover between 1 and {} rows", ROWS));
+    }
+    let tiles = difficulty.tiles();
+    match picks.iter().find(|&&tile| tile >= tiles) {
+        Some(tile) => Err(format!(
+            "pick {} is out of range, {} rows have tiles 0 to {}",
+            tile,
+            difficulty,
+            tiles - 1
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Simulates a game of Dragon Tower.
+///
+/// # Example
+///
+/// ```
+/// use fair::games::dragon_tower::{self, Difficulty};
+/// use fair::ProvablyFairConfig;
+///
+/// let config = ProvablyFairConfig::new("some client seed", "some server seed", 1);
+/// let result = dragon_tower::simulate(config, Difficulty::Medium);
+/// let climb = result.climb(&[0, 1, 2]).unwrap();
+/// ```
+///
+pub fn simulate(config: ProvablyFairConfig, difficulty: Difficulty) -> SimulationResult {
+    let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config);
+
+    let rows = (0..ROWS)
+        .map(|_| {
+            let mut remaining_tiles: Vec<u8> = (0..difficulty.tiles()).collect();
+            (0..difficulty.eggs())
+                .map(|_| {
+                    let f = rng.next().unwrap();
+                    let idx = (f * remaining_tiles.len() as f64) as usize;
+                    remaining_tiles.remove(idx)
+                })
+                .collect()
+        })
+        .collect();
+
+    SimulationResult { difficulty, rows }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> ProvablyFairConfig {
+        ProvablyFairConfig::new("client seed", "server seed", 1)
+    }
+
+    #[test]
+    fn simulate_dragon_tower() {
+        let res = simulate(config(), Difficulty::Master);
+        assert_eq!(res.rows.len(), ROWS);
+        let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config());
+        for row in &res.rows {
+            assert_eq!(row, &vec![(rng.next().unwrap() * 4.) as u8]);
+        }
+
+        for &difficulty in &Difficulty::ALL {
+            let res = simulate(config(), difficulty);
+            for row in &res.rows {
+                assert_eq!(row.len(), difficulty.eggs() as usize);
+                let mut tiles = row.clone();
+                tiles.sort_unstable();
+                tiles.dedup();
+                assert_eq!(tiles.len(), row.len(), "{:?}", row);
+                assert!(tiles.iter().all(|&tile| tile < difficulty.tiles()));
+            }
+        }
+    }
+
+    #[test]
+    fn dragon_tower_multipliers() {
+        assert_eq!(Difficulty::Easy.multiplier(1), 1.31);
+        assert_eq!(Difficulty::Easy.multiplier(9), 13.05);
+        assert_eq!(Difficulty::Medium.multiplier(1), 1.47);
+        assert_eq!(Difficulty::Medium.multiplier(9), 37.67);
+        assert_eq!(Difficulty::Hard.multiplier(9), 501.76);
+        assert_eq!(Difficulty::Expert.multiplier(1), 2.94);
+        assert_eq!(Difficulty::Master.multiplier(9), 256901.12);
+    }
+
+    #[test]
+    fn dragon_tower_climb() {
+        let res = simulate(config(), Difficulty::Hard);
+        let eggs: Vec<u8> = res.rows.iter().map(|row| row[0]).collect();
+
+        let climb = res.climb(&eggs[..3]).unwrap();
+        assert_eq!((climb.rows, climb.busted), (3, false));
+        assert_eq!(climb.multiplier, 7.84);
+        assert_eq!(climb.to_string(), "Cashed out after row 3, payout: 7.84x");
+
+        let mut picks = eggs.clone();
+        picks[4] = 1 - picks[4];
+        let climb = res.climb(&picks).unwrap();
+        assert_eq!((climb.rows, climb.busted, climb.multiplier), (4, true, 0.));
+        assert_eq!(climb.to_string(), "No egg on row 5, payout: 0x");
+
+        assert_eq!(res.climb(&eggs).unwrap().multiplier, 501.76);
+        assert!(res.climb(&[]).is_err());
+        assert!(res.climb(&[0; 10]).is_err());
+        assert_eq!(
+            res.climb(&[2]).unwrap_err(),
+            "pick 2 is out of range, hard rows have tiles 0 to 1"
+        );
+    }
+
+    #[test]
+    fn parse_difficulty() {
+        assert_eq!("easy".parse(), Ok(Difficulty::Easy));
+        assert_eq!("MASTER".parse(), Ok(Difficulty::Master));
+        assert!("extreme".parse::<Difficulty>().is_err());
+        for difficulty in &Difficulty::ALL {
+            assert_eq!(difficulty.to_string().parse(), Ok(*difficulty));
+        }
+    }
+}
@@ -7,7 +7,8 @@ use std::env;
 use std::error::Error; use std::fs;
 */
 
-use crate::card::Deck;
+pub use super::Dealing;
+use crate::card::{Dealer, Deck};
 pub use crate::rng::{ProvablyFairConfig, ProvablyFairRNG};
 use serde::Serialize;
 
@@ -18,15 +19,24 @@ pub struct SimulationResult {
     deck: Deck,
 }
 
+/// Cards are printed in ASCII with `{:#}`.
 impl fmt::Display for SimulationResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.deck)
+        if f.alternate() {
+            write!(f, "{:#}", self.deck)
+        } else {
+            write!(f, "{}", self.deck)
+        }
     }
 }
 
 pub fn simulate(config: ProvablyFairConfig) -> SimulationResult {
-    let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config);
-    let deck = Deck::from_rng(&mut rng, 52);
+    simulate_with_dealing(config, Dealing::Infinite)
+}
+
+/// Same as `simulate` but the cards can be dealt from a finite shoe, e.g. `Dealing::Shoe(1)`.
+pub fn simulate_with_dealing(config: ProvablyFairConfig, dealing: Dealing) -> SimulationResult {
+    let deck = Dealer::new(config, dealing).deal_deck(52);
 
     SimulationResult { deck }
 }
@@ -52,4 +62,17 @@ mod test {
 
         assert_eq!(format!("{}", result), "♦9 - ♠9 - ♦A - ♠A - ♦J - ♠K - ♦Q - ♣A - ♦3 - ♥10 - ♥10 - ♥5 - ♦J - ♦7 - ♦K - ♣6 - ♠Q - ♥7 - ♦4 - ♠3 - ♣3 - ♠Q - ♠5 - ♠8 - ♦10 - ♠3 - ♥Q - ♣8 - ♣10 - ♠9 - ♥7 - ♣J - ♥5 - ♠K - ♣2 - ♦3 - ♦A - ♣J - ♣3 - ♥A - ♦10 - ♠5 - ♣K - ♥K - ♣4 - ♦8 - ♦10 - ♠9 - ♣K - ♠9 - ♣3 - ♦5");
     }
+
+    #[test]
+    fn simulate_hilo_shoe() {
+        let config = ProvablyFairConfig::new("client seed", "server seed", 1);
+        let deck = simulate_with_dealing(config, Dealing::Shoe(1))
+            .deck
+            .to_string();
+        let mut cards: Vec<&str> = deck.split(" - ").collect();
+        cards.sort_unstable();
+        cards.dedup();
+        // a single deck deals every card once
+        assert_eq!(cards.len(), 52);
+    }
 }
@@ -57,10 +57,51 @@ pub fn simulate(config: ProvablyFairConfig) -> SimulationResult {
     }
 }
 
+/// Checks the number of picked squares is between 1 and 10.
+pub fn validate_picks(picks: u8) -> Result<(), String> {
+    if (1..=10).contains(&picks) {
+        Ok(())
+    } else {
+        Err("picks must be between 1 and 10 inclusive".to_string())
+    }
+}
+
+/// Probability of getting exactly `hits` hits when picking `picks` squares (hypergeometric
+/// distribution: 10 squares are drawn out of 40).
+///
+/// Panics if `picks` is invalid, see `validate_picks`.
+pub fn hits_probability(picks: usize, hits: usize) -> f64 {
+    assert!((1..=10).contains(&picks));
+    if hits > picks {
+        return 0.;
+    }
+    let ways = num_integer::binomial(picks as u64, hits as u64)
+        * num_integer::binomial(40 - picks as u64, 10 - hits as u64);
+    ways as f64 / num_integer::binomial(40_u64, 10) as f64
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_validate_picks() {
+        assert!(validate_picks(1).is_ok());
+        assert!(validate_picks(10).is_ok());
+        assert!(validate_picks(0).is_err());
+        assert!(validate_picks(11).is_err());
+    }
+
+    #[test]
+    fn test_hits_probability() {
+        for picks in 1..=10 {
+            let total: f64 = (0..=picks).map(|hits| hits_probability(picks, hits)).sum();
+            assert!((total - 1.).abs() < 1e-12);
+        }
+        assert_eq!(hits_probability(1, 1), 0.25);
+        assert_eq!(hits_probability(1, 2), 0.);
+    }
+
     #[test]
     fn simulate_plinko_test() {
         let config = ProvablyFairConfig::new("client seed", "server seed", 1);
@@ -24,7 +24,7 @@ pub struct SimulationResult {
 
 impl fmt::Display for SimulationResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut grid = format!("");
+        let mut grid = String::new();
         for idx in 0..25 {
             if idx % 5 == 0 {
                 grid = format!("{}\n", grid);
@@ -38,9 +38,18 @@ impl fmt::Display for SimulationResult {
     }
 }
 
+/// Checks the number of mines is between 1 and 24.
+pub fn validate_mines(mines: u8) -> Result<(), String> {
+    if (1..=24).contains(&mines) {
+        Ok(())
+    } else {
+        Err("mines must be between 1 and 24 inclusive".to_string())
+    }
+}
+
+/// Panics if `total_mines` is invalid, see `validate_mines`.
 pub fn simulate(config: ProvablyFairConfig, total_mines: u8) -> SimulationResult {
-    assert!(total_mines >= 1);
-    assert!(total_mines <= 24);
+    validate_mines(total_mines).unwrap_or_else(|e| panic!("{}", e));
     let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config);
 
     let mut remaining_squares: Vec<u8> = (0..25).collect();
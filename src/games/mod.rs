@@ -3,12 +3,17 @@ pub mod blackjack;
 pub mod crash;
 pub mod diamond_poker;
 pub mod dice;
+pub mod dragon_tower;
 pub mod hilo;
 pub mod keno;
 pub mod limbo;
 pub mod mines;
 pub mod plinko;
+mod risk;
 pub mod roulette;
 pub mod slots;
 pub mod video_poker;
 pub mod wheel;
+
+pub use crate::card::{Dealing, Shoe};
+pub use risk::Risk;
@@ -23,6 +23,8 @@ use std::fmt;
 pub struct SimulationResult {
     pub payout: f64,
     pub index: usize,
+    /// direction taken by the ball at each row
+    pub path: Vec<Direction>,
 }
 
 impl fmt::Display for SimulationResult {
@@ -31,41 +33,57 @@ impl fmt::Display for SimulationResult {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum Risk {
-    Low,
-    Medium,
-    High,
-}
-impl Risk {
-    pub fn from_str(s: &str) -> Risk {
-        match &s.to_lowercase()[..] {
-            "low" => Risk::Low,
-            "medium" => Risk::Medium,
-            "high" => Risk::High,
-            _ => panic!("invalid risk string {}", s),
-        }
+pub use super::Risk;
+
+/// Checks the number of rows of pins is between 8 and 16.
+pub fn validate_rows(rows: u8) -> Result<(), String> {
+    if (8..=16).contains(&rows) {
+        Ok(())
+    } else {
+        Err("rows must be between 8 and 16 inclusive".to_string())
     }
 }
 
+#[derive(Debug, Copy, Clone)]
 pub struct Opts {
     risk: Risk,
     rows: u8,
 }
 
-impl Opts {
-    pub fn default() -> Opts {
-        Self::new(8, Risk::Low)
+impl Default for Opts {
+    fn default() -> Opts {
+        Opts {
+            risk: Risk::Low,
+            rows: 8,
+        }
     }
+}
+
+impl Opts {
+    /// Panics if `rows` is invalid, use `with_rows` to validate options given by the user.
     pub fn new(rows: u8, risk: Risk) -> Opts {
-        assert!(rows >= 8);
-        assert!(rows <= 16);
-        Opts { risk, rows }
+        Opts::default()
+            .with_rows(rows)
+            .unwrap_or_else(|e| panic!("{}", e))
+            .with_risk(risk)
+    }
+    pub fn with_rows(self, rows: u8) -> Result<Opts, String> {
+        validate_rows(rows)?;
+        Ok(Opts { rows, ..self })
+    }
+    pub fn with_risk(self, risk: Risk) -> Opts {
+        Opts { risk, ..self }
+    }
+    pub fn rows(&self) -> u8 {
+        self.rows
+    }
+    pub fn risk(&self) -> Risk {
+        self.risk
     }
 }
 
-#[derive(Copy, Clone)]
-enum Direction {
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum Direction {
     Left,
     Right,
 }
@@ -90,17 +108,20 @@ fn get_direction(rng: &mut ProvablyFairRNG<f64>) -> Direction {
 ///
 pub fn simulate(config: ProvablyFairConfig, opts: Option<Opts>) -> SimulationResult {
     let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config);
-    let opts = opts.unwrap_or(Opts::default());
+    let opts = opts.unwrap_or_default();
 
     let total: usize = (3 + opts.rows as usize) * 2 - 1;
     let middle: usize = (total / 2) + 1;
 
     let mut idx: i32 = middle as i32;
+    let mut path = Vec::with_capacity(opts.rows as usize);
     for _ in 0..opts.rows {
-        idx += match get_direction(&mut rng) {
+        let direction = get_direction(&mut rng);
+        idx += match direction {
             Left => -1,
             Right => 1,
-        }
+        };
+        path.push(direction);
     }
     idx = idx / 2 - 1;
 
@@ -109,6 +130,7 @@ pub fn simulate(config: ProvablyFairConfig, opts: Option<Opts>) -> SimulationRes
     SimulationResult {
         payout,
         index: idx as usize,
+        path,
     }
 }
 
@@ -217,7 +239,7 @@ fn slot_payout(rows: usize, risk: &Risk, slot_index: usize) -> f64 {
         // 5.6, 2.1, 1.1, 1, 0.5, 1, 1.1, 2.1, 5.6
         // Whereas for 9 rows, payout row is (0.7 is repeated):
         // 5.6, 2, 1.6, 1, 0.7, 0.7, 1, 1.6, 2, 5.6
-        let repeat = if rows % 2 == 0 { 0 } else { 1 };
+        let repeat = if rows.is_multiple_of(2) { 0 } else { 1 };
         last_idx - (slot_index - last_idx) + repeat
     } else {
         slot_index
@@ -236,8 +258,7 @@ pub fn slot_probability(rows: usize, slot_index: usize) -> f64 {
     let binom = num_integer::binomial(rows, k) as f64;
     let k = k as f64;
 
-    let prob = binom as f64 * p.powf(k) * (1. - p).powf(n - k);
-    prob
+    binom * p.powf(k) * (1. - p).powf(n - k)
 }
 
 pub fn compute_expected_value(rows: usize, risk: &Risk) -> f64 {
@@ -276,6 +297,26 @@ mod test {
         assert_eq!(simulate(config, Some(Opts::new(9, Risk::Low))).index, 6);
     }
 
+    #[test]
+    fn plinko_opts() {
+        let opts = Opts::default().with_rows(16).unwrap().with_risk(Risk::High);
+        assert_eq!((opts.rows(), opts.risk()), (16, Risk::High));
+        assert_eq!(
+            Opts::default().with_rows(17).unwrap_err(),
+            "rows must be between 8 and 16 inclusive"
+        );
+    }
+
+    #[test]
+    fn simulate_plinko_path() {
+        let config = ProvablyFairConfig::new("client seed", "server seed", 2);
+        let result = simulate(config, None);
+        assert_eq!(result.path.len(), 8);
+        // the slot is the number of times the ball went right
+        let rights = result.path.iter().filter(|&&d| d == Right).count();
+        assert_eq!(rights, result.index);
+    }
+
     /*
     #[test]
     fn test_expected_values_once() {
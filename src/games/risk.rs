@@ -0,0 +1,56 @@
+//! # risk level
+//!
+//! Shared by the games whose payouts depend on a risk level (plinko and wheel).
+
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum Risk {
+    Low,
+    Medium,
+    High,
+}
+
+impl Risk {
+    /// Every risk level, from lowest to highest.
+    pub const ALL: [Risk; 3] = [Risk::Low, Risk::Medium, Risk::High];
+    /// Names accepted by `from_str`, in the same order as `ALL`.
+    pub const NAMES: [&'static str; 3] = ["low", "medium", "high"];
+}
+
+impl FromStr for Risk {
+    type Err = String;
+
+    /// Parses a risk level, ignoring case.
+    fn from_str(s: &str) -> Result<Risk, String> {
+        Risk::NAMES
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(s))
+            .map(|idx| Risk::ALL[idx])
+            .ok_or_else(|| "risk must be one of low, medium or high".to_string())
+    }
+}
+
+impl fmt::Display for Risk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let idx = Risk::ALL.iter().position(|risk| risk == self).unwrap();
+        write!(f, "{}", Risk::NAMES[idx])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_risk() {
+        assert_eq!("low".parse(), Ok(Risk::Low));
+        assert_eq!("HIGH".parse(), Ok(Risk::High));
+        assert!("extreme".parse::<Risk>().is_err());
+        for risk in &Risk::ALL {
+            assert_eq!(risk.to_string().parse(), Ok(*risk));
+        }
+    }
+}
@@ -6,7 +6,8 @@ use std::env;
 use std::error::Error; use std::fs;
 */
 
-use crate::card::Deck;
+pub use super::Dealing;
+use crate::card::{Dealer, Deck};
 pub use crate::rng::{ProvablyFairConfig, ProvablyFairRNG};
 
 use serde::Serialize;
@@ -18,21 +19,35 @@ pub struct SimulationResult {
     coming_cards: Deck,
 }
 
+/// Cards are printed in ASCII with `{:#}`.
 impl fmt::Display for SimulationResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Initial Hand: {}\nComing Cards: {}",
-            self.initial_hand, self.coming_cards
-        )
+        if f.alternate() {
+            write!(
+                f,
+                "Initial Hand: {:#}\nComing Cards: {:#}",
+                self.initial_hand, self.coming_cards
+            )
+        } else {
+            write!(
+                f,
+                "Initial Hand: {}\nComing Cards: {}",
+                self.initial_hand, self.coming_cards
+            )
+        }
     }
 }
 
 /// Simulates a game of video poker.
 pub fn simulate(config: ProvablyFairConfig) -> SimulationResult {
-    let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config);
-    let initial_hand = Deck::from_rng(&mut rng, 5);
-    let coming_cards = Deck::from_rng(&mut rng, 5);
+    simulate_with_dealing(config, Dealing::Infinite)
+}
+
+/// Same as `simulate` but the cards can be dealt from a finite shoe, e.g. `Dealing::Shoe(1)`.
+pub fn simulate_with_dealing(config: ProvablyFairConfig, dealing: Dealing) -> SimulationResult {
+    let mut dealer = Dealer::new(config, dealing);
+    let initial_hand = dealer.deal_deck(5);
+    let coming_cards = dealer.deal_deck(5);
 
     // keep track of drawn cards
     SimulationResult {
@@ -21,42 +21,55 @@ impl fmt::Display for SimulationResult {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize)]
-pub enum Risk {
-    Low,
-    Medium,
-    High,
-}
-impl Risk {
-    pub fn from_str(s: &str) -> Risk {
-        match &s.to_lowercase()[..] {
-            "low" => Risk::Low,
-            "medium" => Risk::Medium,
-            "high" => Risk::High,
-            _ => panic!("invalid risk string {}", s),
-        }
+pub use super::Risk;
+
+/// Numbers of segments a wheel can have.
+pub const SEGMENTS: [u8; 5] = [10, 20, 30, 40, 50];
+
+pub fn validate_segments(segments: u8) -> Result<(), String> {
+    if SEGMENTS.contains(&segments) {
+        Ok(())
+    } else {
+        Err("segments must be one of 10, 20, 30, 40 or 50".to_string())
     }
 }
 
+#[derive(Debug, Copy, Clone)]
 pub struct Opts {
     risk: Risk,
     segments: u8,
 }
 
-pub fn validate_segments(segments: u8) -> bool {
-    match segments {
-        10 | 20 | 30 | 40 | 50 => true,
-        _ => false,
+impl Default for Opts {
+    fn default() -> Opts {
+        Opts {
+            risk: Risk::Low,
+            segments: 10,
+        }
     }
 }
 
 impl Opts {
-    pub fn default() -> Opts {
-        Self::new(10, Risk::Low)
-    }
+    /// Panics if `segments` is invalid, use `with_segments` to validate options given by the
+    /// user.
     pub fn new(segments: u8, risk: Risk) -> Opts {
-        assert!(validate_segments(segments));
-        Opts { risk, segments }
+        Opts::default()
+            .with_segments(segments)
+            .unwrap_or_else(|e| panic!("{}", e))
+            .with_risk(risk)
+    }
+    pub fn with_segments(self, segments: u8) -> Result<Opts, String> {
+        validate_segments(segments)?;
+        Ok(Opts { segments, ..self })
+    }
+    pub fn with_risk(self, risk: Risk) -> Opts {
+        Opts { risk, ..self }
+    }
+    pub fn segments(&self) -> u8 {
+        self.segments
+    }
+    pub fn risk(&self) -> Risk {
+        self.risk
     }
 }
 
@@ -78,17 +91,14 @@ fn get_payout_slice(segments: u8, risk: Risk) -> &'static [f64] {
 
 pub fn simulate(config: ProvablyFairConfig, opts: Option<Opts>) -> SimulationResult {
     let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::from_config(config);
-    let opts = opts.unwrap_or(Opts::default());
+    let opts = opts.unwrap_or_default();
 
     let payouts = get_payout_slice(opts.segments, opts.risk);
 
     let idx = (rng.next().unwrap() * payouts.len() as f64) as usize;
     let payout = payouts[idx];
 
-    SimulationResult {
-        payout,
-        index: idx as usize,
-    }
+    SimulationResult { payout, index: idx }
 }
 
 #[cfg(test)]
@@ -118,4 +128,11 @@ mod test {
         assert_eq!(index, 21);
         assert_eq!(payout, 0.);
     }
+
+    #[test]
+    fn wheel_opts() {
+        let opts = Opts::default().with_segments(50).unwrap();
+        assert_eq!((opts.segments(), opts.risk()), (50, Risk::Low));
+        assert!(Opts::default().with_segments(15).is_err());
+    }
 }
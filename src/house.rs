@@ -0,0 +1,158 @@
+//! # house side of provably fair games
+//!
+//! Reference implementation of what a casino does: generate random seeds, publish the hash of
+//! the server seed before any bet, play bets with incrementing nonces and reveal the server seed
+//! when the seed pair is rotated. Enabled with the `house` cargo feature.
+
+use crate::rng::ProvablyFairConfig;
+use crate::session::{SeedPair, Session};
+use crate::utils;
+use serde::Serialize;
+
+/// Random server seed: 32 bytes from the operating system's secure RNG, hex encoded.
+pub fn generate_server_seed() -> String {
+    random_hex(32)
+}
+
+/// Random client seed, for players who don't pick their own.
+pub fn generate_client_seed() -> String {
+    random_hex(8)
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0; len];
+    getrandom::fill(&mut bytes).expect("could not get random bytes from the operating system");
+    hex::encode(bytes)
+}
+
+/// Whether a revealed server seed matches the hash committed to before the bets.
+pub fn verify_reveal(seed_pair: &SeedPair) -> bool {
+    utils::hash_server_seed(&seed_pair.server_seed) == seed_pair.server_seed_hash
+}
+
+/// What the player gets back for each bet: enough to verify it once the server seed is revealed.
+#[derive(Debug, Clone, Serialize)]
+pub struct Receipt<T> {
+    pub client_seed: String,
+    pub server_seed_hash: String,
+    pub nonce: u64,
+    pub outcome: T,
+}
+
+pub struct House {
+    session: Session,
+}
+
+impl House {
+    /// Starts with a random server seed and the player's `client_seed`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fair::games;
+    /// use fair::house::{self, House};
+    ///
+    /// let mut house = House::new(&house::generate_client_seed());
+    /// let commitment = house.server_seed_hash().to_string();
+    /// let receipt = house.bet(games::dice::simulate);
+    /// println!("{} (nonce {})", receipt.outcome, receipt.nonce);
+    ///
+    /// let revealed = house.rotate("new client seed");
+    /// assert_eq!(revealed.server_seed_hash, commitment);
+    /// assert!(house::verify_reveal(&revealed));
+    /// ```
+    ///
+    pub fn new(client_seed: &str) -> House {
+        House {
+            session: Session::new(client_seed, &generate_server_seed()),
+        }
+    }
+
+    /// Commitment to publish before the first bet of the current seed pair.
+    pub fn server_seed_hash(&self) -> &str {
+        self.session.server_seed_hash()
+    }
+
+    pub fn client_seed(&self) -> &str {
+        self.session.client_seed()
+    }
+
+    /// Nonce of the next bet.
+    pub fn nonce(&self) -> u64 {
+        self.session.nonce()
+    }
+
+    /// Plays the next bet with `game`, e.g. `games::dice::simulate`.
+    pub fn bet<T, F>(&mut self, game: F) -> Receipt<T>
+    where
+        F: FnOnce(ProvablyFairConfig) -> T,
+    {
+        let nonce = self.session.nonce();
+        let outcome = game(self.session.next_config());
+        Receipt {
+            client_seed: self.client_seed().to_string(),
+            server_seed_hash: self.server_seed_hash().to_string(),
+            nonce,
+            outcome,
+        }
+    }
+
+    /// Switches to a new random server seed and `client_seed`, revealing the previous pair.
+    pub fn rotate(&mut self, client_seed: &str) -> SeedPair {
+        self.session
+            .rotate(client_seed, &generate_server_seed())
+            .clone()
+    }
+
+    /// Seed pairs revealed so far, oldest first.
+    pub fn revealed(&self) -> &[SeedPair] {
+        self.session.history()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::games::{dice, roulette};
+
+    #[test]
+    fn test_generate_seeds() {
+        let seed = generate_server_seed();
+        assert_eq!(seed.len(), 64);
+        assert_ne!(seed, generate_server_seed());
+        assert_eq!(generate_client_seed().len(), 16);
+    }
+
+    #[test]
+    fn test_end_to_end() {
+        let mut house = House::new("client seed");
+        let commitment = house.server_seed_hash().to_string();
+        let receipts: Vec<_> = (0..3).map(|_| house.bet(dice::simulate)).collect();
+        let pocket = house.bet(roulette::simulate).outcome.pocket;
+        assert_eq!(house.nonce(), 4);
+
+        let revealed = house.rotate("new client seed");
+        assert!(verify_reveal(&revealed));
+        assert_eq!(revealed.server_seed_hash, commitment);
+        assert_ne!(house.server_seed_hash(), commitment);
+        assert_eq!(house.nonce(), 0);
+        assert_eq!(house.revealed().len(), 1);
+        assert_eq!(house.revealed()[0], revealed);
+
+        // the player can now replay every bet
+        for receipt in &receipts {
+            assert_eq!(receipt.server_seed_hash, commitment);
+            let config =
+                ProvablyFairConfig::new(&receipt.client_seed, &revealed.server_seed, receipt.nonce);
+            assert_eq!(dice::simulate(config).outcome, receipt.outcome.outcome);
+        }
+        let config = ProvablyFairConfig::new("client seed", &revealed.server_seed, 3);
+        assert_eq!(roulette::simulate(config).pocket, pocket);
+
+        let tampered = SeedPair {
+            server_seed: "other seed".to_string(),
+            ..revealed
+        };
+        assert!(!verify_reveal(&tampered));
+    }
+}
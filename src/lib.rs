@@ -8,10 +8,23 @@ use std::error::Error; use std::fs;
 */
 // use std::process;
 
-mod card;
+pub mod card;
 pub mod games;
+#[cfg(feature = "house")]
+pub mod house;
 mod rng;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod session;
+pub mod stats;
+pub mod strategy;
 pub mod utils;
+// public on wasm32 for the `tests/web.rs` suite only, JS uses the wasm-bindgen exports
+#[cfg(target_arch = "wasm32")]
+#[doc(hidden)]
+pub mod wasm;
+#[cfg(not(target_arch = "wasm32"))]
 mod wasm;
 
 pub use rng::{ProvablyFairConfig, ProvablyFairRNG};
+pub use session::Session;
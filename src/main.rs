@@ -1,77 +1,105 @@
 use clap::*;
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
 use std::process;
+use std::time::Instant;
 
 use fair::games::*;
+use fair::stats;
 use fair::utils;
 use fair::ProvablyFairConfig;
 
-use hex;
+mod man;
+mod profile;
+mod seeds;
+#[cfg(feature = "tui")]
+mod tui;
+
+// games a strategy can bet on, only dice, limbo, roulette, plinko and wheel without a script
+const STRATEGY_GAMES: [&str; 14] = [
+    "baccarat",
+    "blackjack",
+    "diamond_poker",
+    "dice",
+    "dragon_tower",
+    "hilo",
+    "keno",
+    "limbo",
+    "mines",
+    "plinko",
+    "roulette",
+    "slots",
+    "video_poker",
+    "wheel",
+];
 
 // TODO: implement game as subcommands? cause plinko games has some additional parameters (e.g.
 // risk and rows)
 // TODO: add --json flag to output result as json
 
 // TODO: refactor so that client_seed, server_see and nonce required for all games except crash
-fn main() {
-    let app = clap_app!(myapp =>
+fn app() -> App<'static, 'static> {
+    clap_app!(myapp =>
         (name: crate_name!())
         (version: crate_version!())
         (author: crate_authors!())
         (about: crate_description!())
+        (@arg profile: --profile +takes_value +global
+             "Profile of the config file to take seeds and options from. Positional arguments \
+              then fill in from the right, e.g. `fair dice 42 --profile stake` only sets the nonce")
+        (@arg config: --config +takes_value +global
+             "Config file (default: $FAIR_CONFIG or ~/.config/fair/config.toml)")
         (@subcommand baccarat =>
             (about: "Baccarat game")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
+
+            (args: &card_args())
         )
         (@subcommand dice =>
             (about: "Dice game")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
         )
         (@subcommand limbo =>
             (about: "Limbo game")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
         )
         (@subcommand hilo =>
             (about: "Hilo game")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
+
+            (args: &card_args())
         )
         (@subcommand blackjack =>
             (about: "Blackjack")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
+
+            (args: &card_args())
         )
         (@subcommand diamond_poker =>
             (about: "Diamond poker")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
         )
         (@subcommand roulette =>
             (about: "Roulette")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
         )
         (@subcommand plinko =>
             (about: "Plinko game")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
 
             (@arg risk: --risk +takes_value
                  default_value("low")
-                 possible_value[low]
-                 possible_value[medium]
-                 possible_value[high]
-                 "Risk")
+                 {validate_risk}
+                 "Risk (low, medium or high)")
             (@arg rows: --rows +takes_value
                  default_value("8")
                  {validate_plinko_rows}
@@ -79,97 +107,358 @@ fn main() {
         )
         (@subcommand keno =>
             (about: "Keno")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
         )
         (@subcommand mines =>
             (about: "Mines game")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
 
             (@arg mines: --mines +takes_value
                  default_value("3")
                  {validate_mines_mines}
                  "Number of Mines")
         )
+        (@subcommand dragon_tower =>
+            (about: "Dragon Tower game")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
+
+            (@arg difficulty: --difficulty +takes_value
+                 default_value("easy")
+                 {validate_difficulty}
+                 "Difficulty (easy, medium, hard, expert or master)")
+            (@arg climb: --climb +takes_value +use_delimiter
+                 "Tiles picked from the bottom row up, starting at 0 (e.g. 0,2,1), prints the payout of cashing out")
+        )
         (@subcommand video_poker =>
             (about: "Video Poker")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
+
+            (args: &card_args())
         )
         (@subcommand wheel =>
             (about: "Wheel game")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
 
             (@arg risk: --risk +takes_value
                  default_value("low")
-                 possible_value[low]
-                 possible_value[medium]
-                 possible_value[high]
-                 "Risk")
+                 {validate_risk}
+                 "Risk (low, medium or high)")
             (@arg segments: --segments +takes_value
                  default_value("10")
-                 possible_value("10")
-                 possible_value("20")
-                 possible_value("30")
-                 possible_value("40")
-                 possible_value("50")
-                 "Segments")
+                 {validate_wheel_segments}
+                 "Segments (10, 20, 30, 40 or 50)")
         )
         (@subcommand slots =>
             (about: "Slots game(s)")
-            (@arg client_seed: +required "Client seed")
-            (@arg server_seed: +required "Server seed")
-            (@arg nonce: +required "Nonce (positive integer)")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
 
             (@arg round: --round +takes_value
                  default_value("0")
                  "Round #")
         )
+        (@subcommand stats =>
+            (about: "Statistical tests of the RNG output for a seed pair")
+            (args: &seed_positionals())
+            (@arg nonce: "Nonce (positive integer)")
+
+            (@arg samples: --samples +takes_value
+                 default_value("100000")
+                 {validate_stats_samples}
+                 "Number of bytes and floats to draw")
+            (@arg across_nonces: --("across-nonces")
+                 "Draw the first value of consecutive nonces instead of consecutive values of a single nonce")
+            (@arg range: --range +takes_value +multiple number_of_values(1)
+                 {validate_stats_range}
+                 "Check uniformity of float * range, at most --samples (default: 2, 7, 37 and 52)")
+        )
+        (@subcommand distribution =>
+            (about: "Compares the outcome distribution of a game over many nonces with theory")
+            (@arg game: +required
+                 possible_value[plinko]
+                 possible_value[wheel]
+                 possible_value[roulette]
+                 possible_value[keno]
+                 possible_value[baccarat]
+                 "Game")
+            (args: &seed_positionals())
+            (@arg nonce: "First nonce (positive integer)")
+
+            (@arg nonces: --nonces +takes_value
+                 default_value("10000")
+                 "Number of consecutive nonces to simulate")
+            (@arg rows: --rows +takes_value
+                 default_value("8")
+                 {validate_plinko_rows}
+                 "Plinko rows")
+            (@arg segments: --segments +takes_value
+                 default_value("10")
+                 {validate_wheel_segments}
+                 "Wheel segments (10, 20, 30, 40 or 50)")
+            (@arg picks: --picks +takes_value
+                 default_value("10")
+                 {validate_keno_picks}
+                 "Number of keno squares picked")
+            (@arg all: --all "Test every configuration of the game")
+        )
+        (@subcommand strategy =>
+            (about: "Simulates a betting strategy over consecutive nonces and tracks the bankroll")
+            (@arg game: +required
+                 {validate_strategy_game}
+                 "Game (dice, limbo, roulette, plinko or wheel, or any game with a --script defining payout(result))")
+            (args: &seed_positionals())
+            (@arg nonce: "First nonce (positive integer)")
+
+            (@arg strategy: --strategy +takes_value
+                 default_value("flat")
+                 possible_value[flat]
+                 possible_value[martingale]
+                 possible_value[dalembert]
+                 possible_value[paroli]
+                 possible_value[fraction]
+                 "Betting strategy")
+            (@arg script: --script +takes_value
+                 "Rhai script defining a next_bet(state) function, replaces --strategy")
+            (@arg base_bet: --("base-bet") +takes_value
+                 default_value("1")
+                 {validate_positive_number}
+                 "Base bet")
+            (@arg fraction: --fraction +takes_value
+                 default_value("0.01")
+                 {validate_fraction}
+                 "Fraction of the bankroll bet by the fraction strategy")
+            (@arg bankroll: --bankroll +takes_value
+                 default_value("100")
+                 {validate_positive_number}
+                 "Starting bankroll")
+            (@arg bets: --bets +takes_value
+                 default_value("1000")
+                 "Maximum number of bets per session")
+            (@arg stop_loss: --("stop-loss") +takes_value
+                 {validate_positive_number}
+                 "Stop once this amount has been lost")
+            (@arg take_profit: --("take-profit") +takes_value
+                 {validate_positive_number}
+                 "Stop once this amount has been won")
+            (@arg sessions: --sessions +takes_value
+                 default_value("1")
+                 "Number of consecutive sessions to simulate (Monte-Carlo)")
+            (@arg curve: --curve "Print every bet as csv instead of a summary")
+            (@arg target: --target +takes_value
+                 {validate_positive_number}
+                 "Dice roll target (default: 50.5) or limbo multiplier target (default: 2)")
+            (@arg under: --under "Dice: roll under the target instead of over")
+            (@arg pockets: --pockets +takes_value +multiple number_of_values(1)
+                 "Roulette pockets bet on (default: red)")
+            (@arg rows: --rows +takes_value
+                 default_value("8")
+                 {validate_plinko_rows}
+                 "Plinko rows")
+            (@arg segments: --segments +takes_value
+                 default_value("10")
+                 {validate_wheel_segments}
+                 "Wheel segments (10, 20, 30, 40 or 50)")
+            (@arg risk: --risk +takes_value
+                 default_value("low")
+                 {validate_risk}
+                 "Plinko and wheel risk (low, medium or high)")
+            (@arg mines: --mines +takes_value
+                 default_value("3")
+                 {validate_mines_mines}
+                 "Number of Mines, for scripts")
+            (@arg difficulty: --difficulty +takes_value
+                 default_value("easy")
+                 {validate_difficulty}
+                 "Dragon Tower difficulty (easy, medium, hard, expert or master), for scripts")
+            (@arg decks: --decks +takes_value
+                 default_value("0")
+                 {validate_decks}
+                 "Number of decks in the shoe of card games, for scripts")
+        )
+        (@subcommand tui =>
+            (about: "Interactive terminal UI to step through nonces (requires the `tui` feature)")
+            (args: &seed_positionals())
+            (@arg nonce: default_value("0") "First nonce (positive integer)")
+
+            (@arg game: --game +takes_value
+                 default_value("dice")
+                 possible_value[baccarat]
+                 possible_value[blackjack]
+                 possible_value[diamond_poker]
+                 possible_value[dice]
+                 possible_value[dragon_tower]
+                 possible_value[hilo]
+                 possible_value[keno]
+                 possible_value[limbo]
+                 possible_value[mines]
+                 possible_value[plinko]
+                 possible_value[roulette]
+                 possible_value[slots]
+                 possible_value[video_poker]
+                 possible_value[wheel]
+                 "Game shown first")
+            (@arg hash: --hash +takes_value
+                 {validate_hex}
+                 "Hashed server seed the casino committed to")
+        )
+        (@subcommand serve =>
+            (about: "Serves a local JSON HTTP API (requires the `server` feature)")
+            (@arg listen: --listen +takes_value
+                 default_value("127.0.0.1:8080")
+                 "Address to listen on")
+        )
+        (@subcommand completions =>
+            (about: "Prints the completion script of a shell, e.g. `fair completions bash > /etc/bash_completion.d/fair`")
+            (@arg shell: +required possible_values(&Shell::variants()) "Shell")
+        )
+        (@subcommand man =>
+            (about: "Prints the man page, e.g. `fair man > /usr/local/share/man/man1/fair.1`")
+        )
         (@subcommand crash =>
             (about: "Crash game (uses Stake.com's parameters).")
             (@arg verify: --verify "Verify whether the hash is valid (can be slow)")
-            (@arg game_hash: +required
+            (@arg history: --history +takes_value
+                 {validate_history}
+                 "Number of preceding games to derive from the game hash")
+            (@arg analyze: --analyze requires[history]
+                 "Print distribution statistics of the preceding games instead of listing them")
+            (@arg threshold: --threshold +takes_value +multiple number_of_values(1)
+                 {validate_threshold}
+                 "Crash point threshold used by --analyze (default: 1.01, 2 and 10)")
+            (@arg format: --format +takes_value
+                 default_value("text")
+                 possible_value[text]
+                 possible_value[csv]
+                 possible_value[json]
+                 "Output format of --history and --analyze")
+            (@arg game_hash: +required +multiple
                  {validate_hex}
-                 "Game hash")
+                 "Game hash(es), multiple hashes are verified in parallel")
         )
     )
-    .setting(clap::AppSettings::ArgRequiredElseHelp);
+    .setting(clap::AppSettings::ArgRequiredElseHelp)
+}
 
-    let matches = app.get_matches();
+fn main() {
+    let matches = parse_args(std::env::args_os().collect());
 
     let (game, maybe_sub_matches) = matches.subcommand();
     let sub_matches = maybe_sub_matches.unwrap();
 
     match game {
+        "serve" => serve(sub_matches.value_of("listen").unwrap()),
+        "tui" => tui(sub_matches),
+        "completions" => {
+            let shell = value_t!(sub_matches, "shell", Shell).unwrap_or_else(|e| e.exit());
+            app().gen_completions_to(crate_name!(), shell, &mut io::stdout());
+        }
+        "man" => print!(
+            "{}",
+            man::render(
+                &app(),
+                crate_name!(),
+                crate_version!(),
+                crate_description!(),
+                crate_authors!()
+            )
+        ),
         "crash" => {
-            // TODO: ensure game_hash is valid hex with hex::decode
-            let game_hash = sub_matches.value_of("game_hash").unwrap();
-
-            let game_hash = crash::Hash::from_hex(game_hash);
+            let game_hashes: Vec<_> = sub_matches
+                .values_of("game_hash")
+                .unwrap()
+                .map(crash::Hash::from_hex)
+                .collect();
+            let game_hash = game_hashes[0];
             let config = crash::Config::for_stake();
-            println!("{}", crash::simulate(config, game_hash));
+            let format = sub_matches.value_of("format").unwrap();
+            if sub_matches.is_present("history") && game_hashes.len() > 1 {
+                die("--history only supports a single game hash");
+            }
+            // the preceding games are only worth analyzing or listing once the hash is valid
+            let pipeable = sub_matches.is_present("analyze")
+                || (sub_matches.is_present("history") && format != "text");
+            if pipeable
+                && sub_matches.is_present("verify")
+                && !verify_crash_hashes(config, &game_hashes)[0]
+            {
+                die("!!! Game hash is INVALID !!!");
+            }
+            if sub_matches.is_present("analyze") {
+                let count = value_t!(sub_matches, "history", usize).unwrap_or_else(|e| e.exit());
+                let thresholds = match values_t!(sub_matches, "threshold", f64) {
+                    Ok(thresholds) => thresholds,
+                    Err(_) => crash::analysis::DEFAULT_THRESHOLDS.to_vec(),
+                };
+                let crash_points: Vec<_> = crash::history(config, game_hash)
+                    .skip(1)
+                    .take(count)
+                    .map(|(_, crash_point)| crash_point)
+                    .collect();
+                let report =
+                    crash::analysis::analyze(&crash_points, config.house_edge(), &thresholds);
+                match format {
+                    "json" => println!("{}", serde_json::to_string_pretty(&report).unwrap()),
+                    "csv" => print!("{}", report.to_csv()),
+                    _ => println!("{}", report),
+                }
+                return;
+            }
+            if sub_matches.is_present("history") && format != "text" {
+                // only print the history so the output can be piped to other tools
+                let count = value_t!(sub_matches, "history", usize).unwrap_or_else(|e| e.exit());
+                print_crash_history(
+                    crash::history(config, game_hash).skip(1).take(count),
+                    format,
+                );
+                return;
+            }
+            if game_hashes.len() == 1 {
+                println!("{}", crash::simulate(config, game_hash));
+            } else {
+                for &game_hash in &game_hashes {
+                    println!("{}: {}", game_hash, crash::simulate(config, game_hash));
+                }
+            }
+            if sub_matches.is_present("history") {
+                let count = value_t!(sub_matches, "history", usize).unwrap_or_else(|e| e.exit());
+                // skip the game itself, we only want the ones before it
+                let games = crash::history(config, game_hash).skip(1).take(count);
+                println!("\nPrevious games:\n");
+                print_crash_history(games, format);
+            }
             if sub_matches.is_present("verify") {
                 println!("\nVerifying game hash, this could take a while...\n");
-                if crash::verify_hash(config, game_hash) {
-                    println!("Game hash is valid.");
+                let results = verify_crash_hashes(config, &game_hashes);
+                if game_hashes.len() == 1 {
+                    if results[0] {
+                        println!("Game hash is valid.");
+                    } else {
+                        die("!!! Game hash is INVALID !!!");
+                    }
                 } else {
-                    die("!!! Game hash is INVALID !!!");
+                    for (game_hash, &valid) in game_hashes.iter().zip(&results) {
+                        let status = if valid { "valid" } else { "INVALID" };
+                        println!("{}: {}", game_hash, status);
+                    }
+                    if results.contains(&false) {
+                        die("\n!!! Some game hashes are INVALID !!!");
+                    }
+                    println!("\nAll game hashes are valid.");
                 }
             } else {
-                println!("");
+                println!();
                 println!("IMPORTANT: use --verify to verify the game hash is valid");
             }
         }
         _ => {
             // let game = matches.value_of("game").unwrap().to_lowercase();
-            let client_seed = sub_matches.value_of("client_seed").unwrap();
-            let server_seed = sub_matches.value_of("server_seed").unwrap();
+            let (client_seed, server_seed, nonce) = seed_args(sub_matches);
+            let (client_seed, server_seed) = (client_seed.as_str(), server_seed.as_str());
             // println!("{:?}", matches);
 
             // println!("Hashed server seed: {}\n", hash);
@@ -178,50 +467,131 @@ fn main() {
 
             // TODO: list supported games!
             // TODO use value_t! to parse game.. ensure game is in valid list of strings...
-            let nonce: u64 = value_t!(sub_matches, "nonce", u64).unwrap_or_else(|e| e.exit());
             // println!("{:?}", matches);
             let config = ProvablyFairConfig::new(client_seed, server_seed, nonce);
             let res = match game {
-                "baccarat" => baccarat::simulate(config).to_string(),
+                "baccarat" => format_cards(
+                    baccarat::simulate_with_dealing(config, dealing(sub_matches)),
+                    sub_matches,
+                ),
                 "dice" => dice::simulate(config).to_string(),
                 "limbo" => limbo::simulate(config).to_string(),
-                "hilo" => hilo::simulate(config).to_string(),
-                "blackjack" => blackjack::simulate(config).to_string(),
+                "hilo" => format_cards(
+                    hilo::simulate_with_dealing(config, dealing(sub_matches)),
+                    sub_matches,
+                ),
+                "blackjack" => format_cards(
+                    blackjack::simulate_with_dealing(config, dealing(sub_matches)),
+                    sub_matches,
+                ),
                 "diamond_poker" => diamond_poker::simulate(config).to_string(),
                 "roulette" => roulette::simulate(config).to_string(),
-                "plinko" => {
-                    let rows: u8 = value_t!(sub_matches, "rows", u8).unwrap_or_else(|e| e.exit());
-                    let risk = sub_matches.value_of("risk").unwrap_or("low");
-
-                    let risk = match risk {
-                        "low" => plinko::Risk::Low,
-                        "medium" => plinko::Risk::Medium,
-                        "high" => plinko::Risk::High,
-                        _ => panic!("Uknown risk {}", risk),
-                    };
-
-                    let opts = plinko::Opts::new(rows, risk);
-                    plinko::simulate(config, Some(opts)).to_string()
-                }
+                "plinko" => plinko::simulate(config, Some(plinko_opts(sub_matches))).to_string(),
                 "keno" => keno::simulate(config).to_string(),
                 "mines" => {
                     let mines: u8 = value_t!(sub_matches, "mines", u8).unwrap_or_else(|e| e.exit());
                     mines::simulate(config, mines).to_string()
                 }
-                "video_poker" => video_poker::simulate(config).to_string(),
-                "wheel" => {
-                    let segments: u8 =
-                        value_t!(sub_matches, "segments", u8).unwrap_or_else(|e| e.exit());
-                    let risk = sub_matches.value_of("risk").unwrap_or("low");
-                    let risk = wheel::Risk::from_str(risk);
-                    let opts = wheel::Opts::new(segments, risk);
-                    wheel::simulate(config, Some(opts)).to_string()
+                "dragon_tower" => {
+                    let difficulty = or_die(sub_matches.value_of("difficulty").unwrap().parse());
+                    let res = dragon_tower::simulate(config, difficulty);
+                    if sub_matches.is_present("climb") {
+                        let picks =
+                            values_t!(sub_matches, "climb", u8).unwrap_or_else(|e| e.exit());
+                        format!("{}\n\n{}", res, or_die(res.climb(&picks)))
+                    } else {
+                        res.to_string()
+                    }
                 }
+                "video_poker" => format_cards(
+                    video_poker::simulate_with_dealing(config, dealing(sub_matches)),
+                    sub_matches,
+                ),
+                "wheel" => wheel::simulate(config, Some(wheel_opts(sub_matches))).to_string(),
                 "slots" => {
                     let round: usize =
                         value_t!(sub_matches, "round", usize).unwrap_or_else(|e| e.exit());
                     slots::simulate(config, round).to_string()
                 }
+                "stats" => {
+                    let samples: usize =
+                        value_t!(sub_matches, "samples", usize).unwrap_or_else(|e| e.exit());
+                    let ranges = match values_t!(sub_matches, "range", usize) {
+                        Ok(ranges) => ranges,
+                        Err(_) => stats::rng::DEFAULT_RANGES.to_vec(),
+                    };
+                    for &range in &ranges {
+                        or_die(stats::rng::validate_range(range, samples));
+                    }
+                    let mut sampler = stats::rng::Sampler::new(client_seed, server_seed, nonce);
+                    if sub_matches.is_present("across_nonces") {
+                        sampler = sampler.across_nonces();
+                    }
+                    stats::rng::run(&sampler, samples, &ranges).to_string()
+                }
+                "distribution" => {
+                    let nonces: usize =
+                        value_t!(sub_matches, "nonces", usize).unwrap_or_else(|e| e.exit());
+                    let game = sub_matches.value_of("game").unwrap();
+                    let configs = if sub_matches.is_present("all") {
+                        stats::games::Game::all_configs(game)
+                    } else {
+                        vec![distribution_game(game, sub_matches)]
+                    };
+                    let reports: Vec<_> = configs
+                        .into_iter()
+                        .map(|config| {
+                            stats::games::run(config, client_seed, server_seed, nonce, nonces)
+                        })
+                        .collect();
+                    for report in &reports {
+                        println!("{}\n", report);
+                    }
+                    let suspicious: Vec<_> = reports
+                        .iter()
+                        .filter(|report| report.is_suspicious())
+                        .map(|report| report.game.clone())
+                        .collect();
+                    if !suspicious.is_empty() {
+                        die(&format!(
+                            "!!! Outcomes deviate from theory for: {} !!!",
+                            suspicious.join(", ")
+                        ));
+                    }
+                    "All outcome distributions are consistent with theory.".to_string()
+                }
+                "strategy" => {
+                    let bankroll: f64 =
+                        value_t!(sub_matches, "bankroll", f64).unwrap_or_else(|e| e.exit());
+                    let bets: usize =
+                        value_t!(sub_matches, "bets", usize).unwrap_or_else(|e| e.exit());
+                    let sessions: usize =
+                        value_t!(sub_matches, "sessions", usize).unwrap_or_else(|e| e.exit());
+                    let play = strategy_player(sub_matches);
+                    if sessions > 1 {
+                        // sessions use consecutive, non-overlapping blocks of nonces
+                        let runs: Vec<_> = (0..sessions as u64)
+                            .map(|i| {
+                                let nonce = nonce + i * bets as u64;
+                                play(client_seed, server_seed, nonce, bankroll, bets)
+                            })
+                            .collect();
+                        fair::strategy::MonteCarlo::from_runs(&runs).to_string()
+                    } else {
+                        let run = play(client_seed, server_seed, nonce, bankroll, bets);
+                        if sub_matches.is_present("curve") {
+                            println!("nonce,amount,multiplier,bankroll");
+                            for b in &run.bets {
+                                println!(
+                                    "{},{},{},{}",
+                                    b.nonce, b.amount, b.multiplier, b.bankroll
+                                );
+                            }
+                            println!();
+                        }
+                        run.to_string()
+                    }
+                }
                 _ => die("This branch should never execute. Unimplemented game?"),
             };
             println!("{}", res);
@@ -229,23 +599,403 @@ fn main() {
     };
 }
 
+// client and server seed positionals of every subcommand taking seeds, before its nonce
+fn seed_positionals() -> [Arg<'static, 'static>; 2] {
+    [
+        Arg::with_name("client_seed").help(
+            "Client seed (`-` reads it from stdin, `@file` from a file, default: $FAIR_CLIENT_SEED)",
+        ),
+        Arg::with_name("server_seed").help(
+            "Server seed (`-` reads it from stdin, `@file` from a file, default: $FAIR_SERVER_SEED)",
+        ),
+    ]
+}
+
+// options of the card games
+fn card_args() -> [Arg<'static, 'static>; 2] {
+    [
+        Arg::with_name("decks")
+            .long("decks")
+            .takes_value(true)
+            .default_value("0")
+            .validator(validate_decks)
+            .help("Number of decks in the shoe, 0 draws every card from a full deck like Stake"),
+        Arg::with_name("ascii")
+            .long("ascii")
+            .help("Print cards in ASCII, e.g. JS instead of ♠J"),
+    ]
+}
+
+// parses the command line, filling in values from the --profile if any
+fn parse_args(args: Vec<OsString>) -> ArgMatches<'static> {
+    let matches = app().get_matches_from(args.clone());
+    let (name, sub_matches) = matches.subcommand();
+    let sub_matches = match sub_matches {
+        Some(sub_matches) => sub_matches,
+        None => return matches,
+    };
+    // global arguments end up in the subcommand's matches
+    let profile_name = match sub_matches.value_of("profile") {
+        Some(profile_name) => profile_name,
+        None => return matches,
+    };
+    let path = match sub_matches.value_of("config") {
+        Some(path) => PathBuf::from(path),
+        None => profile::default_path()
+            .unwrap_or_else(|| die("could not find the config file, use --config")),
+    };
+    let profile = profile::load(&path, profile_name)
+        .unwrap_or_else(|e| die(&format!("{}: {}", path.display(), e)));
+    // parse again so that profile values are validated like any other argument
+    app().get_matches_from(profile.apply(&args, name, sub_matches))
+}
+
+fn seed_args(sub_matches: &ArgMatches) -> (String, String, u64) {
+    let (client_seed, server_seed, nonce) = seeds::args(sub_matches).unwrap_or_else(|e| die(&e));
+    let nonce = nonce
+        .parse()
+        .unwrap_or_else(|_| die("nonce must be a positive integer"));
+    (client_seed, server_seed, nonce)
+}
+
+// Game options are validated by the library so that the CLI, the server and wasm reject them
+// with the same message. Numbers that aren't a `u8` become `invalid`, which the library rejects.
+fn to_u8(number: &str, invalid: u8) -> u8 {
+    number.parse().unwrap_or(invalid)
+}
+
 fn validate_plinko_rows(rows: String) -> std::result::Result<(), String> {
-    let rows: u8 = rows.parse().unwrap_or(0);
-    if rows >= 8 && rows <= 16 {
+    plinko::validate_rows(to_u8(&rows, 0))
+}
+
+fn validate_wheel_segments(segments: String) -> std::result::Result<(), String> {
+    wheel::validate_segments(to_u8(&segments, 0))
+}
+
+fn validate_risk(risk: String) -> std::result::Result<(), String> {
+    risk.parse::<Risk>().map(|_| ())
+}
+
+fn validate_strategy_game(game: String) -> std::result::Result<(), String> {
+    if STRATEGY_GAMES.contains(&game.as_str()) {
         Ok(())
     } else {
-        Err("must be between 8 to 16 inclusive".to_string())
+        Err(format!("game must be one of {}", STRATEGY_GAMES.join(", ")))
     }
 }
 
+fn validate_difficulty(difficulty: String) -> std::result::Result<(), String> {
+    difficulty.parse::<dragon_tower::Difficulty>().map(|_| ())
+}
+
 fn validate_mines_mines(mines: String) -> std::result::Result<(), String> {
-    let mines: u8 = mines.parse().unwrap_or(0);
-    if mines >= 1 && mines <= 24 {
-        Ok(())
+    mines::validate_mines(to_u8(&mines, 0))
+}
+
+// 0 is a valid number of decks
+fn validate_decks(decks: String) -> std::result::Result<(), String> {
+    Dealing::from_decks(to_u8(&decks, u8::MAX)).map(|_| ())
+}
+
+// options missing from subcommands without a risk (e.g. distribution) keep their default
+fn plinko_opts(sub_matches: &ArgMatches) -> plinko::Opts {
+    let mut opts = plinko::Opts::default();
+    if let Some(rows) = sub_matches.value_of("rows") {
+        opts = or_die(opts.with_rows(to_u8(rows, 0)));
+    }
+    if let Some(risk) = sub_matches.value_of("risk") {
+        opts = opts.with_risk(or_die(risk.parse()));
+    }
+    opts
+}
+
+fn dealing(sub_matches: &ArgMatches) -> Dealing {
+    let decks = sub_matches.value_of("decks").unwrap();
+    or_die(Dealing::from_decks(to_u8(decks, u8::MAX)))
+}
+
+// `--ascii` prints the cards without suit symbols
+fn format_cards<T: std::fmt::Display>(res: T, sub_matches: &ArgMatches) -> String {
+    if sub_matches.is_present("ascii") {
+        format!("{:#}", res)
     } else {
-        Err("must be between 1 to 24 inclusive".to_string())
+        res.to_string()
+    }
+}
+
+fn wheel_opts(sub_matches: &ArgMatches) -> wheel::Opts {
+    let mut opts = wheel::Opts::default();
+    if let Some(segments) = sub_matches.value_of("segments") {
+        opts = or_die(opts.with_segments(to_u8(segments, 0)));
+    }
+    if let Some(risk) = sub_matches.value_of("risk") {
+        opts = opts.with_risk(or_die(risk.parse()));
+    }
+    opts
+}
+
+// verifies game hashes in parallel while reporting progress on stderr
+fn verify_crash_hashes(config: crash::Config, game_hashes: &[crash::Hash]) -> Vec<bool> {
+    let start = Instant::now();
+    let results = crash::verify_hashes(config, game_hashes, |progress| {
+        let elapsed = start.elapsed().as_secs_f64();
+        let remaining = (progress.total - progress.walked) as f64;
+        // hashes that are valid stop early so this is only an upper bound
+        let eta = (elapsed / progress.walked as f64 * remaining) as u64;
+        eprint!(
+            "\rWalked {}/{} hashes ({}%), ETA: {}m{:02}s   ",
+            progress.walked,
+            progress.total,
+            progress.walked * 100 / progress.total,
+            eta / 60,
+            eta % 60
+        );
+        true
+    });
+    // clear progress line
+    eprint!("\r{:70}\r", "");
+    // never cancelled
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+fn distribution_game(game: &str, sub_matches: &ArgMatches) -> stats::games::Game {
+    match game {
+        "plinko" => stats::games::Game::Plinko(plinko_opts(sub_matches)),
+        "wheel" => stats::games::Game::Wheel(wheel_opts(sub_matches)),
+        "keno" => {
+            let picks: u8 = value_t!(sub_matches, "picks", u8).unwrap_or_else(|e| e.exit());
+            stats::games::Game::Keno { picks }
+        }
+        "roulette" => stats::games::Game::Roulette,
+        "baccarat" => stats::games::Game::Baccarat,
+        _ => die("This branch should never execute. Unimplemented game?"),
+    }
+}
+
+fn strategy_bet(sub_matches: &ArgMatches) -> fair::strategy::Bet {
+    use fair::strategy::Bet;
+    let target = value_t!(sub_matches, "target", f64).ok();
+    match sub_matches.value_of("game").unwrap() {
+        "dice" => {
+            let target = target.unwrap_or(50.5);
+            if target <= 0. || target >= 100. {
+                die("--target must be between 0 and 100 for dice");
+            }
+            Bet::Dice {
+                target,
+                over: !sub_matches.is_present("under"),
+            }
+        }
+        "limbo" => Bet::Limbo {
+            target: target.unwrap_or(2.),
+        },
+        "roulette" => {
+            let pockets = match values_t!(sub_matches, "pockets", u8) {
+                Ok(pockets) => pockets,
+                Err(_) => fair::strategy::ROULETTE_RED.to_vec(),
+            };
+            if pockets.iter().any(|&pocket| pocket > 36) {
+                die("--pockets must be between 0 and 36");
+            }
+            Bet::Roulette { pockets }
+        }
+        "plinko" => Bet::Plinko(plinko_opts(sub_matches)),
+        "wheel" => Bet::Wheel(wheel_opts(sub_matches)),
+        game => die(&format!(
+            "only dice, limbo, roulette, plinko and wheel have built-in bets, bet on {} with a --script defining payout(result)",
+            game
+        )),
+    }
+}
+
+type Player = Box<dyn Fn(&str, &str, u64, f64, usize) -> fair::strategy::Run>;
+
+// plays either the --script or the built-in --strategy
+fn strategy_player(sub_matches: &ArgMatches) -> Player {
+    if let Some(path) = sub_matches.value_of("script") {
+        return script_player(path, sub_matches);
+    }
+    let bet = strategy_bet(sub_matches);
+    let strategy = betting_strategy(sub_matches);
+    Box::new(move |client_seed, server_seed, nonce, bankroll, max_bets| {
+        fair::strategy::run(
+            &bet,
+            &strategy,
+            client_seed,
+            server_seed,
+            nonce,
+            bankroll,
+            max_bets,
+        )
+    })
+}
+
+#[cfg(feature = "script")]
+fn script_player(path: &str, sub_matches: &ArgMatches) -> Player {
+    let source = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| die(&format!("could not read {}: {}", path, e)));
+    let mut script = fair::strategy::script::Script::compile(&source)
+        .unwrap_or_else(|e| die(&format!("invalid script: {}", e)));
+    if let Ok(stop_loss) = value_t!(sub_matches, "stop_loss", f64) {
+        script = script.with_stop_loss(stop_loss);
+    }
+    if let Ok(take_profit) = value_t!(sub_matches, "take_profit", f64) {
+        script = script.with_take_profit(take_profit);
+    }
+    // scripts defining a payout bet on any game, the others on the built-in bets
+    if script.defines_payout() {
+        let simulate = game_result(sub_matches);
+        return Box::new(move |client_seed, server_seed, nonce, bankroll, max_bets| {
+            script
+                .run_game(
+                    &simulate,
+                    client_seed,
+                    server_seed,
+                    nonce,
+                    bankroll,
+                    max_bets,
+                )
+                .unwrap_or_else(|e| die(&format!("script error: {}", e)))
+        });
+    }
+    let bet = strategy_bet(sub_matches);
+    Box::new(move |client_seed, server_seed, nonce, bankroll, max_bets| {
+        script
+            .run(&bet, client_seed, server_seed, nonce, bankroll, max_bets)
+            .unwrap_or_else(|e| die(&format!("script error: {}", e)))
+    })
+}
+
+#[cfg(not(feature = "script"))]
+fn script_player(_path: &str, _sub_matches: &ArgMatches) -> Player {
+    die("--script requires fair to be built with the `script` feature")
+}
+
+// `SimulationResult` of the strategy's game, as given to scripts
+#[cfg(feature = "script")]
+fn game_result(sub_matches: &ArgMatches) -> Box<dyn Fn(ProvablyFairConfig) -> serde_json::Value> {
+    use serde_json::to_value;
+    let dealing = dealing(sub_matches);
+    let mines: u8 = value_t!(sub_matches, "mines", u8).unwrap_or_else(|e| e.exit());
+    let difficulty = or_die(sub_matches.value_of("difficulty").unwrap().parse());
+    let plinko_opts = plinko_opts(sub_matches);
+    let wheel_opts = wheel_opts(sub_matches);
+    let game = sub_matches.value_of("game").unwrap().to_string();
+    Box::new(move |config| {
+        let res = match game.as_str() {
+            "baccarat" => to_value(baccarat::simulate_with_dealing(config, dealing)),
+            "blackjack" => to_value(blackjack::simulate_with_dealing(config, dealing)),
+            "diamond_poker" => to_value(diamond_poker::simulate(config)),
+            "dice" => to_value(dice::simulate(config)),
+            "dragon_tower" => to_value(dragon_tower::simulate(config, difficulty)),
+            "hilo" => to_value(hilo::simulate_with_dealing(config, dealing)),
+            "keno" => to_value(keno::simulate(config)),
+            "limbo" => to_value(limbo::simulate(config)),
+            "mines" => to_value(mines::simulate(config, mines)),
+            "plinko" => to_value(plinko::simulate(config, Some(plinko_opts))),
+            "roulette" => to_value(roulette::simulate(config)),
+            "slots" => to_value(slots::simulate(config, 0)),
+            "video_poker" => to_value(video_poker::simulate_with_dealing(config, dealing)),
+            "wheel" => to_value(wheel::simulate(config, Some(wheel_opts))),
+            _ => die("This branch should never execute. Unimplemented game?"),
+        };
+        res.unwrap()
+    })
+}
+
+fn betting_strategy(sub_matches: &ArgMatches) -> fair::strategy::Strategy {
+    use fair::strategy::Strategy;
+    let base_bet: f64 = value_t!(sub_matches, "base_bet", f64).unwrap_or_else(|e| e.exit());
+    let mut strategy = match sub_matches.value_of("strategy").unwrap() {
+        "flat" => Strategy::flat(base_bet),
+        "martingale" => Strategy::martingale(base_bet),
+        "dalembert" => Strategy::d_alembert(base_bet),
+        "paroli" => Strategy::paroli(base_bet),
+        "fraction" => {
+            let fraction: f64 = value_t!(sub_matches, "fraction", f64).unwrap_or_else(|e| e.exit());
+            Strategy::fixed_fraction(fraction)
+        }
+        _ => die("This branch should never execute. Unimplemented strategy?"),
+    };
+    if let Ok(stop_loss) = value_t!(sub_matches, "stop_loss", f64) {
+        strategy = strategy.with_stop_loss(stop_loss);
+    }
+    if let Ok(take_profit) = value_t!(sub_matches, "take_profit", f64) {
+        strategy = strategy.with_take_profit(take_profit);
+    }
+    strategy
+}
+
+fn validate_positive_number(number: String) -> std::result::Result<(), String> {
+    match number.parse::<f64>() {
+        Ok(number) if number > 0. => Ok(()),
+        _ => Err("must be a positive number".to_string()),
+    }
+}
+
+fn validate_fraction(fraction: String) -> std::result::Result<(), String> {
+    match fraction.parse::<f64>() {
+        Ok(fraction) if fraction > 0. && fraction <= 1. => Ok(()),
+        _ => Err("must be a number between 0 (exclusive) and 1 (inclusive)".to_string()),
+    }
+}
+
+fn validate_keno_picks(picks: String) -> std::result::Result<(), String> {
+    keno::validate_picks(to_u8(&picks, 0))
+}
+
+fn validate_stats_samples(samples: String) -> std::result::Result<(), String> {
+    match samples.parse::<usize>() {
+        Ok(samples) if samples >= 100 => Ok(()),
+        _ => Err("must be an integer greater or equal to 100".to_string()),
+    }
+}
+
+fn validate_stats_range(range: String) -> std::result::Result<(), String> {
+    match range.parse::<usize>() {
+        Ok(range) if range >= 2 => Ok(()),
+        _ => Err("must be an integer greater or equal to 2".to_string()),
     }
 }
+
+fn validate_threshold(threshold: String) -> std::result::Result<(), String> {
+    match threshold.parse::<f64>() {
+        Ok(threshold) if threshold >= 1. => Ok(()),
+        _ => Err("must be a number greater or equal to 1".to_string()),
+    }
+}
+
+fn validate_history(count: String) -> std::result::Result<(), String> {
+    match count.parse::<usize>() {
+        Ok(count) if count >= 1 => Ok(()),
+        _ => Err("must be a positive integer".to_string()),
+    }
+}
+
+fn print_crash_history<I: Iterator<Item = (crash::Hash, f64)>>(games: I, format: &str) {
+    match format {
+        "csv" => {
+            println!("game_hash,crash_point");
+            for (game_hash, crash_point) in games {
+                println!("{},{}", game_hash, crash_point);
+            }
+        }
+        "json" => {
+            let games: Vec<_> = games
+                .map(|(game_hash, crash_point)| {
+                    serde_json::json!({ "game_hash": game_hash, "crash_point": crash_point })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&games).unwrap());
+        }
+        _ => {
+            for (game_hash, crash_point) in games {
+                println!("{} {}", game_hash, crash_point);
+            }
+        }
+    }
+}
+
 fn validate_hex(hex: String) -> std::result::Result<(), String> {
     if hex.len() != 64 {
         return Err("must be 64 characters hexadecimal".to_string());
@@ -256,7 +1006,44 @@ fn validate_hex(hex: String) -> std::result::Result<(), String> {
     }
 }
 
+#[cfg(feature = "tui")]
+fn tui(sub_matches: &ArgMatches) {
+    let (client_seed, server_seed, nonce) = seed_args(sub_matches);
+    let app = tui::App::new(
+        &client_seed,
+        &server_seed,
+        nonce,
+        sub_matches.value_of("game").unwrap(),
+        sub_matches.value_of("hash"),
+    );
+    if let Err(e) = tui::run(app) {
+        die(&format!("terminal error: {}", e));
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn tui(_sub_matches: &ArgMatches) {
+    die("fair tui requires fair to be built with the `tui` feature");
+}
+
+#[cfg(feature = "server")]
+fn serve(addr: &str) {
+    println!("Listening on http://{}", addr);
+    if let Err(e) = fair::server::serve(addr) {
+        die(&format!("could not serve on {}: {}", addr, e));
+    }
+}
+
+#[cfg(not(feature = "server"))]
+fn serve(_addr: &str) {
+    die("fair serve requires fair to be built with the `server` feature");
+}
+
 fn die(msg: &str) -> ! {
     eprintln!("{}", msg);
     process::exit(1);
 }
+
+fn or_die<T>(res: std::result::Result<T, String>) -> T {
+    res.unwrap_or_else(|e| die(&e))
+}
@@ -0,0 +1,84 @@
+//! # man page
+//!
+//! Renders the man page of the CLI from its clap definition, with the help of every subcommand
+//! as a section, so it can't drift from the real arguments.
+
+use clap::App;
+
+// wide enough for the option descriptions, narrow enough for a terminal
+const WIDTH: usize = 80;
+
+/// Renders the man page of `app`. The name, version, about and author come from the caller (e.g.
+/// `crate_name!()`) as clap doesn't expose the ones of an `App`.
+pub fn render(app: &App, name: &str, version: &str, about: &str, author: &str) -> String {
+    let mut page = format!(
+        ".TH {} 1 \"\" \"{} {}\"\n",
+        name.to_uppercase(),
+        name,
+        version
+    );
+    page += &format!(".SH NAME\n{} \\- {}\n", name, escape(about));
+    page += &format!(
+        ".SH SYNOPSIS\n\\fB{}\\fR [OPTIONS] <SUBCOMMAND> [ARGS]\n",
+        name
+    );
+    page += &format!(".SH OPTIONS\n{}", help(app.clone()));
+    page += ".SH SUBCOMMANDS\n";
+    // clap 2 has no public accessor for the subcommands, its own completion generators read them
+    // from the parser too: clap is pinned in Cargo.toml so that this can't break in an update
+    for subcommand in &app.p.subcommands {
+        let bin_name = format!("{} {}", name, subcommand.get_name());
+        page += &format!(".SS {}\n", bin_name);
+        page += &help(subcommand.clone().bin_name(bin_name));
+    }
+    page += &format!(".SH AUTHOR\n{}\n", escape(author));
+    page
+}
+
+// help message of `app` without its name and version, as preformatted text
+fn help(app: App) -> String {
+    let mut help = vec![];
+    app.set_term_width(WIDTH)
+        .template("{about}\n\nUSAGE:\n    {usage}\n\n{all-args}")
+        .write_help(&mut help)
+        .expect("could not write the help message");
+    format!(".nf\n{}\n.fi\n", escape(&String::from_utf8(help).unwrap()))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e")
+        .lines()
+        .map(|line| {
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let page = render(&crate::app(), "fair", "1.2.3", "Verifies games.", "Someone");
+        assert!(page
+            .starts_with(".TH FAIR 1 \"\" \"fair 1.2.3\"\n.SH NAME\nfair \\- Verifies games.\n"));
+        assert!(page.ends_with(".SH AUTHOR\nSomeone\n"));
+        assert!(page.contains(".SS fair plinko\n"));
+        assert!(page.contains("--risk <risk>"));
+        assert!(page.contains("Segments (10, 20, 30, 40 or 50)"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(".hidden\n'quote\nC:\\"),
+            "\\&.hidden\n\\&'quote\nC:\\e"
+        );
+    }
+}
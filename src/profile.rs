@@ -0,0 +1,322 @@
+//! # profiles
+//!
+//! Named sets of seeds and default options stored in a TOML config file, so they don't have to
+//! be typed on every command:
+//!
+//! ```toml
+//! [profiles.stake]
+//! client_seed = "client seed"
+//! server_seed = "server seed"
+//! risk = "high"
+//! rows = 16
+//! ```
+//!
+//! Arguments given on the command line always win over the profile.
+
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Positional arguments shared by every game, in order.
+const SEEDS: [&str; 3] = ["client_seed", "server_seed", "nonce"];
+
+/// Subcommands without seeds.
+const SEEDLESS: [&str; 4] = ["completions", "crash", "man", "serve"];
+
+/// Subcommands whose first positional argument is the game, before the seeds.
+const GAME_FIRST: [&str; 2] = ["distribution", "strategy"];
+
+/// Options a profile can set, named like the command line flags.
+const OPTIONS: [&str; 9] = [
+    "risk",
+    "rows",
+    "segments",
+    "mines",
+    "round",
+    "picks",
+    "decks",
+    "difficulty",
+    "format",
+];
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub client_seed: Option<String>,
+    pub server_seed: Option<String>,
+    pub nonce: Option<u64>,
+    pub risk: Option<String>,
+    pub rows: Option<u8>,
+    pub segments: Option<u8>,
+    pub mines: Option<u8>,
+    pub round: Option<usize>,
+    pub picks: Option<u8>,
+    pub decks: Option<u8>,
+    pub difficulty: Option<String>,
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// `$FAIR_CONFIG`, or `config.toml` in the `fair` directory of the user's config directory.
+pub fn default_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("FAIR_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("fair").join("config.toml"))
+}
+
+pub fn load(path: &Path, name: &str) -> Result<Profile, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse(&content, name)
+}
+
+fn parse(content: &str, name: &str) -> Result<Profile, String> {
+    let mut config: ConfigFile = toml::from_str(content).map_err(|e| e.to_string())?;
+    config
+        .profiles
+        .remove(name)
+        .ok_or_else(|| format!("no profile named {}", name))
+}
+
+impl Profile {
+    /// Value of an argument as it would be typed on the command line.
+    pub fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "client_seed" => self.client_seed.clone(),
+            "server_seed" => self.server_seed.clone(),
+            "nonce" => self.nonce.map(|nonce| nonce.to_string()),
+            "risk" => self.risk.clone(),
+            "rows" => self.rows.map(|rows| rows.to_string()),
+            "segments" => self.segments.map(|segments| segments.to_string()),
+            "mines" => self.mines.map(|mines| mines.to_string()),
+            "round" => self.round.map(|round| round.to_string()),
+            "picks" => self.picks.map(|picks| picks.to_string()),
+            "decks" => self.decks.map(|decks| decks.to_string()),
+            "difficulty" => self.difficulty.clone(),
+            "format" => self.format.clone(),
+            _ => None,
+        }
+    }
+
+    /// Adds the profile's values missing from the command line `args` of `subcommand`.
+    ///
+    /// Seeds given on the command line are taken to be the last ones (e.g. only the nonce), so
+    /// the leading ones are inserted right after the subcommand, or after its game. Options are only added to
+    /// subcommands that have them, which are the ones giving them a default value.
+    pub fn apply(
+        &self,
+        args: &[OsString],
+        subcommand: &str,
+        sub_matches: &ArgMatches,
+    ) -> Vec<OsString> {
+        let given = SEEDS
+            .iter()
+            .filter(|&&name| sub_matches.occurrences_of(name) > 0)
+            .count();
+        let missing: Vec<String> = SEEDS[..SEEDS.len() - given]
+            .iter()
+            .map_while(|name| self.get(name))
+            .collect();
+        // inserting only some of them would shift the given ones to the wrong argument
+        let seeds = if SEEDLESS.contains(&subcommand) {
+            vec![]
+        } else if given == 0 || missing.len() == SEEDS.len() - given {
+            missing
+        } else {
+            vec![]
+        };
+
+        let mut new_args = args.to_vec();
+        // `index_of` counts from the subcommand
+        let position = subcommand_position(args, subcommand)
+            + match sub_matches.index_of("game") {
+                Some(idx) if GAME_FIRST.contains(&subcommand) => idx + 1,
+                _ => 1,
+            };
+        new_args.splice(position..position, seeds.into_iter().map(OsString::from));
+
+        for &name in &OPTIONS {
+            if let Some(value) = self.get(name) {
+                if sub_matches.value_of(name).is_some() && sub_matches.occurrences_of(name) == 0 {
+                    new_args.push(format!("--{}", name).into());
+                    new_args.push(value.into());
+                }
+            }
+        }
+        new_args
+    }
+}
+
+// index of the subcommand in `args`, skipping the values of global options before it
+fn subcommand_position(args: &[OsString], subcommand: &str) -> usize {
+    let mut skip_value = false;
+    for (idx, arg) in args.iter().enumerate().skip(1) {
+        if skip_value {
+            skip_value = false;
+            continue;
+        }
+        match arg.to_str() {
+            Some("--profile") | Some("--config") => skip_value = true,
+            Some(arg) if arg == subcommand => return idx,
+            _ => {}
+        }
+    }
+    panic!("subcommand {} not found in the arguments", subcommand)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [profiles.stake]
+        client_seed = "client seed"
+        server_seed = "server seed"
+        nonce = 3
+        rows = 16
+
+        [profiles.seeds_only]
+        client_seed = "client seed"
+        server_seed = "server seed"
+    "#;
+
+    fn apply(profile: &str, args: &[&str]) -> Vec<String> {
+        let profile = parse(CONFIG, profile).unwrap();
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        let matches = crate::app().get_matches_from(args.clone());
+        let (subcommand, sub_matches) = matches.subcommand();
+        profile
+            .apply(&args, subcommand, sub_matches.unwrap())
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let profile = parse(CONFIG, "stake").unwrap();
+        assert_eq!(profile.get("rows"), Some("16".to_string()));
+        assert_eq!(profile.get("risk"), None);
+        assert!(parse(CONFIG, "other").is_err());
+        assert!(parse("[profiles.x]\nunknown = 1", "x").is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(
+            apply("stake", &["fair", "--profile", "stake", "plinko"]),
+            vec![
+                "fair",
+                "--profile",
+                "stake",
+                "plinko",
+                "client seed",
+                "server seed",
+                "3",
+                "--rows",
+                "16"
+            ]
+        );
+        // the nonce and rows given on the command line win
+        assert_eq!(
+            apply(
+                "stake",
+                &["fair", "plinko", "7", "--rows", "8", "--profile", "stake"]
+            ),
+            vec![
+                "fair",
+                "plinko",
+                "client seed",
+                "server seed",
+                "7",
+                "--rows",
+                "8",
+                "--profile",
+                "stake"
+            ]
+        );
+        assert_eq!(
+            apply(
+                "stake",
+                &["fair", "crash", "--profile", "stake", &"ab".repeat(32)]
+            )
+            .len(),
+            5
+        );
+        // the game of distribution and strategy comes before the seeds
+        assert_eq!(
+            apply(
+                "stake",
+                &["fair", "--profile", "stake", "distribution", "plinko"]
+            ),
+            vec![
+                "fair",
+                "--profile",
+                "stake",
+                "distribution",
+                "plinko",
+                "client seed",
+                "server seed",
+                "3",
+                "--rows",
+                "16"
+            ]
+        );
+        assert_eq!(
+            apply(
+                "seeds_only",
+                &[
+                    "fair",
+                    "strategy",
+                    "--bets",
+                    "5",
+                    "dice",
+                    "7",
+                    "--profile",
+                    "seeds_only"
+                ]
+            ),
+            vec![
+                "fair",
+                "strategy",
+                "--bets",
+                "5",
+                "dice",
+                "client seed",
+                "server seed",
+                "7",
+                "--profile",
+                "seeds_only"
+            ]
+        );
+        // dice has no rows option
+        assert_eq!(
+            apply(
+                "seeds_only",
+                &["fair", "dice", "7", "--profile", "seeds_only"]
+            ),
+            vec![
+                "fair",
+                "dice",
+                "client seed",
+                "server seed",
+                "7",
+                "--profile",
+                "seeds_only"
+            ]
+        );
+    }
+}
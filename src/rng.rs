@@ -9,6 +9,7 @@ use std::marker::PhantomData;
 // Create alias for HMAC-SHA256
 type HmacSha256 = Hmac<Sha256>;
 
+#[derive(Clone)]
 pub struct ProvablyFairConfig {
     client_seed: String,
     server_seed: String,
@@ -97,7 +98,19 @@ impl<T> ProvablyFairRNG<T> {
         self.current_round_mac = Some(result.code());
     }
 
-    fn next_byte(&mut self) -> u8 {
+    /// Moves to the `position`th byte of the stream, e.g. `seek(4)` skips the first float.
+    pub fn seek(&mut self, position: u64) {
+        self.current_round = position / 32;
+        self.current_round_cursor = (position % 32) as usize;
+        self.current_round_mac = None;
+    }
+
+    /// Number of bytes consumed so far (4 per float).
+    pub fn position(&self) -> u64 {
+        self.current_round * 32 + self.current_round_cursor as u64
+    }
+
+    pub(crate) fn next_byte(&mut self) -> u8 {
         // 32 = number of bytes in self.current_round_buffer (aka size of hmac signature)
         // TODO: use sizeof pragma?
         let mac = match &self.current_round_mac {
@@ -117,18 +130,15 @@ impl<T> ProvablyFairRNG<T> {
         } else {
             self.current_round_cursor += 1;
         }
-        return result;
+        result
     }
 
-    fn next_float(&mut self) -> f64 {
-        let bytes_per_float = 4;
+    pub(crate) fn next_float(&mut self) -> f64 {
         let bytes = &mut [0; 4];
-        for i in 0..bytes_per_float {
-            let byte = self.next_byte();
-            bytes[i] = byte;
+        for byte in bytes.iter_mut() {
+            *byte = self.next_byte();
         }
-        let result = bytes_to_float(bytes);
-        return result;
+        bytes_to_float(bytes)
     }
 }
 
@@ -151,7 +161,7 @@ fn bytes_to_float(bytes: &[u8]) -> f64 {
     let (float, _) = bytes.iter().fold((0., 0.), |(result, i), &value| {
         let value = value as f64;
         let divider = 256_f64.powf(i + 1.);
-        let partial_result = value / divider as f64;
+        let partial_result = value / divider;
         (result + partial_result, i + 1.)
     });
     float
@@ -161,9 +171,14 @@ fn bytes_to_float(bytes: &[u8]) -> f64 {
 impl ProvablyFairRNG<f64> {
     // get a random number in [start, end[ range
     pub fn range(&mut self, start: usize, end: usize) -> usize {
+        self.range_u64(start as u64, end as u64) as usize
+    }
+
+    /// Same as `range` for bounds that may not fit a `usize`, e.g. on wasm32.
+    pub fn range_u64(&mut self, start: u64, end: u64) -> u64 {
         assert!(end > start);
-        let range = (end as i32 - start as i32) as usize;
-        (self.next().unwrap() * range as f64) as usize + start as usize
+        let range = end - start;
+        (self.next().unwrap() * range as f64) as u64 + start
     }
 }
 
@@ -212,6 +227,33 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_rng_seek() {
+        let mut rng: ProvablyFairRNG<u8> = ProvablyFairRNG::new("client", "server", 1);
+        let bytes: Vec<u8> = rng.by_ref().take(70).collect();
+        assert_eq!(rng.position(), 70);
+        for &position in &[0, 5, 31, 32, 33, 64] {
+            rng.seek(position);
+            assert_eq!(rng.position(), position);
+            assert_eq!(rng.next(), Some(bytes[position as usize]));
+        }
+    }
+
+    #[test]
+    fn test_rng_range() {
+        let float = || {
+            ProvablyFairRNG::<f64>::new("client", "server", 1)
+                .next()
+                .unwrap()
+        };
+        let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::new("client", "server", 1);
+        assert_eq!(rng.range(10, 20), (float() * 10.) as usize + 10);
+        // widths that don't fit an i32
+        let mut rng: ProvablyFairRNG<f64> = ProvablyFairRNG::new("client", "server", 1);
+        let end = 1 << 40;
+        assert_eq!(rng.range_u64(0, end), (float() * end as f64) as u64);
+    }
+
     #[test]
     fn test_rng_float_starts_with_0() {
         let client_seed = "83e27f682128eb1852b048203dfd6931";
@@ -0,0 +1,212 @@
+//! # seeds
+//!
+//! Seeds are sensitive until they are rotated, so they don't have to be passed as arguments
+//! where they would show up in `ps` and the shell history:
+//!
+//! - `-` reads the seed from a line of stdin (`fair dice - - 1` reads both)
+//! - `@path` reads it from a file
+//! - seeds missing from the command line are taken from `$FAIR_CLIENT_SEED` and
+//!   `$FAIR_SERVER_SEED`
+//!
+//! Like with profiles, positional arguments fill in from the right, e.g. `fair dice 42` only sets
+//! the nonce when both environment variables are set. Subcommands with a default nonce (`tui`)
+//! take the seeds first, the default nonce applies when fewer than 3 arguments are given.
+
+use clap::ArgMatches;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+
+/// Positional arguments shared by every game, in order.
+const NAMES: [&str; 3] = ["client_seed", "server_seed", "nonce"];
+
+const ENV_VARS: [&str; 2] = ["FAIR_CLIENT_SEED", "FAIR_SERVER_SEED"];
+
+/// Client seed, server seed and nonce of a game subcommand.
+pub fn args(sub_matches: &ArgMatches) -> Result<(String, String, String), String> {
+    let given: Vec<String> = NAMES
+        .iter()
+        .filter(|&&name| sub_matches.occurrences_of(name) > 0)
+        .map(|&name| sub_matches.value_of(name).unwrap().to_string())
+        .collect();
+    let env = [env::var(ENV_VARS[0]).ok(), env::var(ENV_VARS[1]).ok()];
+    // default value of the nonce, if the subcommand has one
+    let default_nonce = match sub_matches.occurrences_of("nonce") {
+        0 => sub_matches.value_of("nonce").map(String::from),
+        _ => None,
+    };
+    let [client_seed, server_seed, nonce] = fill(given, env, default_nonce)?;
+
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+    Ok((
+        read(NAMES[0], &client_seed, &mut stdin)?,
+        read(NAMES[1], &server_seed, &mut stdin)?,
+        nonce,
+    ))
+}
+
+// Places the `given` values in order, skipping the leading seeds set in `env` while there are
+// fewer values than slots to fill. The nonce is only a slot to fill without a `default_nonce`,
+// which is applied once the given values have been placed.
+fn fill(
+    given: Vec<String>,
+    env: [Option<String>; 2],
+    default_nonce: Option<String>,
+) -> Result<[String; 3], String> {
+    let slots = if default_nonce.is_some() { 2 } else { 3 };
+    let mut skip = slots.max(given.len()) - given.len();
+    let mut given = given.into_iter();
+    let mut values = vec![];
+    for (idx, fallback) in env.iter().chain(&[default_nonce]).enumerate() {
+        let value = match fallback {
+            Some(value) if skip > 0 && idx < 2 => {
+                skip -= 1;
+                Some(value.clone())
+            }
+            _ => given.next().or_else(|| fallback.clone()),
+        };
+        values.push(value.ok_or_else(|| match idx {
+            2 => "missing <nonce>: pass it as an argument or set it in a --profile".to_string(),
+            _ => format!(
+                "missing <{}>: pass it as an argument, set it in a --profile or in ${}",
+                NAMES[idx], ENV_VARS[idx]
+            ),
+        })?);
+    }
+    let mut values = values.into_iter();
+    Ok([
+        values.next().unwrap(),
+        values.next().unwrap(),
+        values.next().unwrap(),
+    ])
+}
+
+fn read<R: BufRead>(name: &str, value: &str, stdin: &mut R) -> Result<String, String> {
+    let content = if value == "-" {
+        let mut line = String::new();
+        stdin
+            .read_line(&mut line)
+            .map_err(|e| format!("could not read <{}> from stdin: {}", name, e))?;
+        if line.is_empty() {
+            return Err(format!("no <{}> on stdin", name));
+        }
+        line
+    } else if let Some(path) = value.strip_prefix('@') {
+        fs::read_to_string(path)
+            .map_err(|e| format!("could not read <{}>: {}: {}", name, path, e))?
+    } else {
+        return Ok(value.to_string());
+    };
+    Ok(content.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_fill() {
+        let env = || [Some("client".to_string()), Some("server".to_string())];
+        assert_eq!(
+            fill(strings(&["1"]), env(), None).unwrap(),
+            ["client", "server", "1"]
+        );
+        assert_eq!(
+            fill(strings(&["a", "1"]), env(), None).unwrap(),
+            ["client", "a", "1"]
+        );
+        assert_eq!(
+            fill(strings(&["a", "b", "1"]), env(), None).unwrap(),
+            ["a", "b", "1"]
+        );
+
+        // only the server seed is secret
+        let server_only = || [None, Some("server".to_string())];
+        assert_eq!(
+            fill(strings(&["a", "1"]), server_only(), None).unwrap(),
+            ["a", "server", "1"]
+        );
+        assert!(fill(strings(&["a"]), server_only(), None)
+            .unwrap_err()
+            .contains("<nonce>"));
+        assert!(fill(vec![], server_only(), None)
+            .unwrap_err()
+            .contains("<client_seed>"));
+    }
+
+    #[test]
+    fn test_fill_missing() {
+        // the nonce is what's missing after two seeds, not the client seed
+        let err = fill(strings(&["a", "b"]), [None, None], None).unwrap_err();
+        assert!(err.starts_with("missing <nonce>"), "{}", err);
+        assert!(fill(vec![], [None, None], None)
+            .unwrap_err()
+            .starts_with("missing <client_seed>"));
+        assert!(fill(strings(&["a"]), [None, None], Some("0".to_string()))
+            .unwrap_err()
+            .starts_with("missing <server_seed>"));
+        assert!(fill(vec![], env_both(), None)
+            .unwrap_err()
+            .starts_with("missing <nonce>"));
+    }
+
+    fn env_both() -> [Option<String>; 2] {
+        [Some("client".to_string()), Some("server".to_string())]
+    }
+
+    #[test]
+    fn test_fill_default_nonce() {
+        let nonce = || Some("0".to_string());
+        assert_eq!(
+            fill(strings(&["a"]), [None, Some("server".to_string())], nonce()).unwrap(),
+            ["a", "server", "0"]
+        );
+        // explicit seeds aren't shifted into the nonce by the environment
+        assert_eq!(
+            fill(
+                strings(&["a", "b"]),
+                [None, Some("server".to_string())],
+                nonce()
+            )
+            .unwrap(),
+            ["a", "b", "0"]
+        );
+        assert_eq!(
+            fill(strings(&["a", "b"]), env_both(), nonce()).unwrap(),
+            ["a", "b", "0"]
+        );
+        assert_eq!(
+            fill(strings(&["a", "b", "7"]), env_both(), nonce()).unwrap(),
+            ["a", "b", "7"]
+        );
+        assert_eq!(
+            fill(vec![], env_both(), nonce()).unwrap(),
+            ["client", "server", "0"]
+        );
+        assert_eq!(
+            fill(strings(&["a", "b"]), [None, None], nonce()).unwrap(),
+            ["a", "b", "0"]
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let mut stdin = "client seed\nserver seed\r\n".as_bytes();
+        assert_eq!(read("client_seed", "-", &mut stdin).unwrap(), "client seed");
+        assert_eq!(read("server_seed", "-", &mut stdin).unwrap(), "server seed");
+        assert!(read("server_seed", "-", &mut stdin).is_err());
+        assert_eq!(read("client_seed", "seed", &mut stdin).unwrap(), "seed");
+
+        let path = env::temp_dir().join("fair-test-seed");
+        fs::write(&path, "file seed\n").unwrap();
+        let arg = format!("@{}", path.display());
+        assert_eq!(read("server_seed", &arg, &mut stdin).unwrap(), "file seed");
+        fs::remove_file(&path).unwrap();
+        assert!(read("server_seed", &arg, &mut stdin).is_err());
+    }
+}
@@ -0,0 +1,362 @@
+//! # local JSON HTTP API
+//!
+//! Exposes the verifier over HTTP so other tools can call it directly. Enabled with the `server`
+//! cargo feature.
+//!
+//! - `POST /simulate/{game}` with `client_seed`, `server_seed`, `nonce` and the game's options
+//!   (`rows`, `risk`, `segments`, `mines`, `round`, `decks` or `difficulty`), returns the game's
+//!   `SimulationResult`. Dragon Tower also takes the `climb` of a player and adds its outcome.
+//! - `POST /verify-seed` with `server_seed` and `server_seed_hash`
+//! - `POST /crash/verify` with `game_hash`, returns its crash point and whether it is valid
+//!
+//! Request bodies are limited to `MAX_BODY_SIZE` bytes and at most `MAX_CRASH_VERIFICATIONS` crash
+//! verifications run at once, further ones are answered with a 503.
+
+use crate::games::*;
+use crate::rng::ProvablyFairConfig;
+use crate::utils;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Largest request body accepted, in bytes.
+pub const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Most crash verifications (each walks up to 10,000,000 hashes) running at once.
+pub const MAX_CRASH_VERIFICATIONS: usize = 4;
+
+static CRASH_VERIFICATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// slot of a counting semaphore, released when dropped
+struct Permit(&'static AtomicUsize);
+
+impl Permit {
+    fn acquire(count: &'static AtomicUsize, max: usize) -> Option<Permit> {
+        count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                if n < max {
+                    Some(n + 1)
+                } else {
+                    None
+                }
+            })
+            .ok()
+            .map(|_| Permit(count))
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response { status: 200, body }
+    }
+    fn error(status: u16, message: &str) -> Response {
+        Response {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SimulateRequest {
+    client_seed: String,
+    server_seed: String,
+    nonce: u64,
+    rows: Option<u8>,
+    risk: Option<String>,
+    segments: Option<u8>,
+    mines: Option<u8>,
+    round: Option<usize>,
+    decks: Option<u8>,
+    difficulty: Option<String>,
+    climb: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct VerifySeedRequest {
+    server_seed: String,
+    server_seed_hash: String,
+}
+
+#[derive(Deserialize)]
+struct CrashVerifyRequest {
+    game_hash: String,
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, Response> {
+    serde_json::from_str(body).map_err(|e| Response::error(400, &e.to_string()))
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> Result<Value, Response> {
+    Ok(serde_json::to_value(value).unwrap())
+}
+
+fn bad_request(message: String) -> Response {
+    Response::error(400, &message)
+}
+
+fn parse_risk(risk: &Option<String>) -> Result<Risk, Response> {
+    match risk {
+        Some(risk) => risk.parse().map_err(bad_request),
+        None => Ok(Risk::Low),
+    }
+}
+
+fn simulate(game: &str, body: &str) -> Result<Value, Response> {
+    let req: SimulateRequest = parse(body)?;
+    let config = ProvablyFairConfig::new(&req.client_seed, &req.server_seed, req.nonce);
+    let dealing = || Dealing::from_decks(req.decks.unwrap_or(0)).map_err(bad_request);
+    match game {
+        "baccarat" => to_json(baccarat::simulate_with_dealing(config, dealing()?)),
+        "dice" => to_json(dice::simulate(config)),
+        "limbo" => to_json(limbo::simulate(config)),
+        "hilo" => to_json(hilo::simulate_with_dealing(config, dealing()?)),
+        "blackjack" => to_json(blackjack::simulate_with_dealing(config, dealing()?)),
+        "diamond_poker" => to_json(diamond_poker::simulate(config)),
+        "roulette" => to_json(roulette::simulate(config)),
+        "keno" => to_json(keno::simulate(config)),
+        "video_poker" => to_json(video_poker::simulate_with_dealing(config, dealing()?)),
+        "plinko" => {
+            let opts = plinko::Opts::default()
+                .with_rows(req.rows.unwrap_or(8))
+                .map_err(bad_request)?
+                .with_risk(parse_risk(&req.risk)?);
+            to_json(plinko::simulate(config, Some(opts)))
+        }
+        "wheel" => {
+            let opts = wheel::Opts::default()
+                .with_segments(req.segments.unwrap_or(10))
+                .map_err(bad_request)?
+                .with_risk(parse_risk(&req.risk)?);
+            to_json(wheel::simulate(config, Some(opts)))
+        }
+        "mines" => {
+            let mines = req.mines.unwrap_or(3);
+            mines::validate_mines(mines).map_err(bad_request)?;
+            to_json(mines::simulate(config, mines))
+        }
+        "slots" => to_json(slots::simulate(config, req.round.unwrap_or(0))),
+        "dragon_tower" => {
+            let difficulty = match &req.difficulty {
+                Some(difficulty) => difficulty.parse().map_err(bad_request)?,
+                None => dragon_tower::Difficulty::Easy,
+            };
+            let res = dragon_tower::simulate(config, difficulty);
+            let mut body = to_json(&res)?;
+            if let Some(picks) = &req.climb {
+                body["climb"] = to_json(res.climb(picks).map_err(bad_request)?)?;
+            }
+            Ok(body)
+        }
+        _ => Err(Response::error(404, &format!("unknown game {}", game))),
+    }
+}
+
+fn verify_seed(body: &str) -> Result<Value, Response> {
+    let req: VerifySeedRequest = parse(body)?;
+    let server_seed_hash = utils::hash_server_seed(&req.server_seed);
+    Ok(json!({
+        "valid": server_seed_hash == req.server_seed_hash.to_lowercase(),
+        "server_seed_hash": server_seed_hash,
+    }))
+}
+
+fn crash_verify(body: &str) -> Result<Value, Response> {
+    let req: CrashVerifyRequest = parse(body)?;
+    let game_hash: crash::Hash = req.game_hash.parse().map_err(bad_request)?;
+    let _permit = Permit::acquire(&CRASH_VERIFICATIONS, MAX_CRASH_VERIFICATIONS)
+        .ok_or_else(|| Response::error(503, "too many crash verifications, try again later"))?;
+    let config = crash::Config::for_stake();
+    Ok(json!({
+        "game_hash": game_hash,
+        "crash_point": crash::simulate(config, game_hash).crash_point,
+        "valid": crash::verify_hash(config, game_hash),
+    }))
+}
+
+/// Routes a request to its endpoint.
+pub fn handle(method: &str, path: &str, body: &str) -> Response {
+    let path = path.split('?').next().unwrap();
+    let res = match (method, path) {
+        ("POST", "/verify-seed") => verify_seed(body),
+        ("POST", "/crash/verify") => crash_verify(body),
+        ("POST", path) if path.starts_with("/simulate/") => {
+            simulate(&path["/simulate/".len()..], body)
+        }
+        (_, "/verify-seed") | (_, "/crash/verify") => {
+            Err(Response::error(405, "method not allowed"))
+        }
+        (_, path) if path.starts_with("/simulate/") => {
+            Err(Response::error(405, "method not allowed"))
+        }
+        _ => Err(Response::error(404, "not found")),
+    };
+    match res {
+        Ok(body) => Response::ok(body),
+        Err(res) => res,
+    }
+}
+
+// reads at most `MAX_BODY_SIZE` bytes of body
+fn read_body<R: Read>(reader: R) -> Result<String, Response> {
+    let mut body = vec![];
+    reader
+        .take(MAX_BODY_SIZE as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| Response::error(400, &e.to_string()))?;
+    if body.len() > MAX_BODY_SIZE {
+        let message = format!("body is larger than {} bytes", MAX_BODY_SIZE);
+        return Err(Response::error(413, &message));
+    }
+    String::from_utf8(body).map_err(|e| Response::error(400, &e.to_string()))
+}
+
+/// Serves the API on `addr` (e.g. `127.0.0.1:8080`) until the process is killed. Each request is
+/// handled on its own thread so that a slow one doesn't hold up the others, the slow crash
+/// verifications are capped at `MAX_CRASH_VERIFICATIONS`.
+pub fn serve(addr: &str) -> Result<(), String> {
+    let server = tiny_http::Server::http(addr).map_err(|e| e.to_string())?;
+    for mut request in server.incoming_requests() {
+        thread::spawn(move || {
+            let res = match read_body(request.as_reader()) {
+                Ok(body) => handle(request.method().as_str(), request.url(), &body),
+                Err(res) => res,
+            };
+            let content_type =
+                tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                    .unwrap();
+            let response = tiny_http::Response::from_string(res.body.to_string())
+                .with_status_code(res.status)
+                .with_header(content_type);
+            // the client may have gone away, nothing to do about it
+            let _ = request.respond(response);
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SEEDS: &str =
+        r#"{ "client_seed": "client seed", "server_seed": "server seed", "nonce": 1 "#;
+
+    fn body(extra: &str) -> String {
+        format!("{}{}}}", SEEDS, extra)
+    }
+
+    #[test]
+    fn test_simulate() {
+        let res = handle("POST", "/simulate/roulette", &body(""));
+        assert_eq!(res.status, 200);
+        assert_eq!(res.body["pocket"], 27);
+
+        let res = handle(
+            "POST",
+            "/simulate/plinko",
+            &body(r#", "rows": 16, "risk": "high""#),
+        );
+        assert_eq!(res.status, 200);
+        let expected = plinko::simulate(
+            ProvablyFairConfig::new("client seed", "server seed", 1),
+            Some(plinko::Opts::new(16, plinko::Risk::High)),
+        );
+        assert_eq!(res.body["payout"], expected.payout);
+
+        let res = handle("POST", "/simulate/plinko", &body(r#", "rows": 20"#));
+        assert_eq!(res.status, 400);
+        let res = handle("POST", "/simulate/wheel", &body(r#", "risk": "extreme""#));
+        assert_eq!(res.status, 400);
+        let res = handle("POST", "/simulate/hilo", &body(r#", "decks": 9"#));
+        assert_eq!(res.status, 400);
+        let res = handle(
+            "POST",
+            "/simulate/dragon_tower",
+            &body(r#", "difficulty": "hard", "climb": [1, 0]"#),
+        );
+        assert_eq!(res.status, 200);
+        assert_eq!(res.body["rows"][0], json!([1]));
+        assert_eq!(res.body["climb"]["busted"], true);
+        let res = handle("POST", "/simulate/dragon_tower", &body(r#", "climb": [4]"#));
+        assert_eq!(res.status, 400);
+        let res = handle("POST", "/simulate/poker", &body(""));
+        assert_eq!(res.status, 404);
+        let res = handle("POST", "/simulate/dice", "{}");
+        assert_eq!(res.status, 400);
+        let res = handle("GET", "/simulate/dice", "");
+        assert_eq!(res.status, 405);
+    }
+
+    #[test]
+    fn test_verify_seed() {
+        let hash = utils::hash_server_seed("server seed");
+        let req = json!({ "server_seed": "server seed", "server_seed_hash": hash }).to_string();
+        let res = handle("POST", "/verify-seed", &req);
+        assert_eq!(
+            res,
+            Response::ok(json!({ "valid": true, "server_seed_hash": hash }))
+        );
+
+        let req = json!({ "server_seed": "other seed", "server_seed_hash": hash }).to_string();
+        assert_eq!(handle("POST", "/verify-seed", &req).body["valid"], false);
+    }
+
+    #[test]
+    fn test_crash_verify() {
+        let res = handle("POST", "/crash/verify", r#"{ "game_hash": "zz" }"#);
+        assert_eq!(res.status, 400);
+        assert_eq!(handle("GET", "/unknown", "").status, 404);
+    }
+
+    #[test]
+    fn test_permit() {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let first = Permit::acquire(&COUNT, 2).unwrap();
+        let _second = Permit::acquire(&COUNT, 2).unwrap();
+        assert!(Permit::acquire(&COUNT, 2).is_none());
+        drop(first);
+        assert!(Permit::acquire(&COUNT, 2).is_some());
+        assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_crash_verify_limit() {
+        let _permits: Vec<Permit> = (0..MAX_CRASH_VERIFICATIONS)
+            .map(|_| Permit::acquire(&CRASH_VERIFICATIONS, MAX_CRASH_VERIFICATIONS).unwrap())
+            .collect();
+        let req = json!({ "game_hash": "ab".repeat(32) }).to_string();
+        let res = handle("POST", "/crash/verify", &req);
+        assert_eq!(res.status, 503);
+        // invalid requests are still rejected right away
+        let res = handle("POST", "/crash/verify", r#"{ "game_hash": "zz" }"#);
+        assert_eq!(res.status, 400);
+    }
+
+    #[test]
+    fn test_read_body() {
+        assert_eq!(read_body(body("").as_bytes()), Ok(body("")));
+        let body = vec![b' '; MAX_BODY_SIZE];
+        assert!(read_body(&body[..]).is_ok());
+        let body = vec![b' '; MAX_BODY_SIZE + 1];
+        let res = read_body(&body[..]).unwrap_err();
+        assert_eq!(res.status, 413);
+        assert_eq!(res.body["error"], "body is larger than 65536 bytes");
+        assert_eq!(read_body(&[0xff][..]).unwrap_err().status, 400);
+    }
+}
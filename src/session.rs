@@ -0,0 +1,159 @@
+//! # seed pair sessions
+//!
+//! Casinos play every bet of a seed pair with an incrementing nonce until the player rotates to a
+//! new pair, at which point the old server seed is revealed. `Session` keeps track of that state
+//! and can be serialized to persist and resume it.
+
+use crate::rng::ProvablyFairConfig;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+
+/// A seed pair and the number of bets played with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedPair {
+    pub client_seed: String,
+    pub server_seed: String,
+    pub server_seed_hash: String,
+    /// nonce of the next bet
+    pub nonce: u64,
+}
+
+impl SeedPair {
+    fn new(client_seed: &str, server_seed: &str, nonce: u64) -> SeedPair {
+        SeedPair {
+            client_seed: client_seed.to_string(),
+            server_seed: server_seed.to_string(),
+            server_seed_hash: utils::hash_server_seed(server_seed),
+            nonce,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    current: SeedPair,
+    /// rotated seed pairs, oldest first
+    previous: Vec<SeedPair>,
+}
+
+impl Session {
+    /// Starts a session whose first bet uses nonce 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fair::{games, Session};
+    ///
+    /// let mut session = Session::new("some client seed", "some server seed");
+    /// let first = games::dice::simulate(session.next_config());
+    /// let second = games::dice::simulate(session.next_config());
+    /// assert_eq!(session.nonce(), 2);
+    ///
+    /// let json = serde_json::to_string(&session).unwrap();
+    /// let mut session: Session = serde_json::from_str(&json).unwrap();
+    /// let revealed = session.rotate("new client seed", "new server seed");
+    /// assert_eq!(revealed.server_seed, "some server seed");
+    /// ```
+    ///
+    pub fn new(client_seed: &str, server_seed: &str) -> Session {
+        Session {
+            current: SeedPair::new(client_seed, server_seed, 0),
+            previous: vec![],
+        }
+    }
+
+    /// Resumes the current seed pair at `nonce`.
+    pub fn with_nonce(self, nonce: u64) -> Session {
+        Session {
+            current: SeedPair {
+                nonce,
+                ..self.current
+            },
+            ..self
+        }
+    }
+
+    pub fn client_seed(&self) -> &str {
+        &self.current.client_seed
+    }
+
+    pub fn server_seed_hash(&self) -> &str {
+        &self.current.server_seed_hash
+    }
+
+    /// Nonce of the next bet.
+    pub fn nonce(&self) -> u64 {
+        self.current.nonce
+    }
+
+    /// Config of the next bet, incrementing the nonce.
+    pub fn next_config(&mut self) -> ProvablyFairConfig {
+        let config = ProvablyFairConfig::new(
+            &self.current.client_seed,
+            &self.current.server_seed,
+            self.current.nonce,
+        );
+        self.current.nonce += 1;
+        config
+    }
+
+    /// Switches to a new seed pair starting at nonce 0 and returns the previous one, whose server
+    /// seed can now be revealed.
+    pub fn rotate(&mut self, client_seed: &str, server_seed: &str) -> &SeedPair {
+        let previous = std::mem::replace(
+            &mut self.current,
+            SeedPair::new(client_seed, server_seed, 0),
+        );
+        self.previous.push(previous);
+        self.previous.last().unwrap()
+    }
+
+    /// Seed pairs rotated away from, oldest first.
+    pub fn history(&self) -> &[SeedPair] {
+        &self.previous
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::games::dice;
+
+    #[test]
+    fn test_next_config() {
+        let mut session = Session::new("client seed", "server seed").with_nonce(1);
+        let outcome = dice::simulate(session.next_config()).outcome;
+        let expected = dice::simulate(ProvablyFairConfig::new("client seed", "server seed", 1));
+        assert_eq!(outcome, expected.outcome);
+        assert_eq!(session.nonce(), 2);
+        assert_eq!(
+            session.server_seed_hash(),
+            utils::hash_server_seed("server seed")
+        );
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut session = Session::new("client seed", "server seed");
+        session.next_config();
+        session.next_config();
+        let revealed = session.rotate("new client seed", "new server seed").clone();
+        assert_eq!(revealed.server_seed, "server seed");
+        assert_eq!(revealed.nonce, 2);
+        assert_eq!(session.client_seed(), "new client seed");
+        assert_eq!(session.nonce(), 0);
+        assert_eq!(session.history(), &[revealed]);
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut session = Session::new("client seed", "server seed");
+        session.next_config();
+        session.rotate("new client seed", "new server seed");
+        session.next_config();
+        let json = serde_json::to_string(&session).unwrap();
+        let resumed: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(resumed, session);
+        assert_eq!(resumed.nonce(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+//! # statistics helpers
+//!
+//! Small set of statistical primitives used to check that simulated outcomes follow their
+//! theoretical distribution.
+
+pub mod games;
+pub mod rng;
+
+use serde::Serialize;
+use std::fmt;
+
+/// Result of a Pearson chi-square goodness-of-fit test.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ChiSquare {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+}
+
+impl fmt::Display for ChiSquare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "chi2 = {:.4} (df = {}), p-value = {:.4}",
+            self.statistic, self.degrees_of_freedom, self.p_value
+        )
+    }
+}
+
+/// Compares observed counts with the counts expected from `probabilities` (which must sum to 1).
+///
+/// # Example
+///
+/// ```
+/// use fair::stats;
+///
+/// let res = stats::chi_square(&[48, 52], &[0.5, 0.5]);
+/// assert!(res.p_value > 0.5);
+/// ```
+///
+pub fn chi_square(observed: &[u64], probabilities: &[f64]) -> ChiSquare {
+    assert_eq!(observed.len(), probabilities.len());
+    assert!(observed.len() >= 2);
+    let total: u64 = observed.iter().sum();
+    let statistic = observed
+        .iter()
+        .zip(probabilities)
+        .map(|(&o, &p)| {
+            let expected = p * total as f64;
+            (o as f64 - expected).powi(2) / expected
+        })
+        .sum();
+    let degrees_of_freedom = observed.len() - 1;
+    ChiSquare {
+        statistic,
+        degrees_of_freedom,
+        p_value: chi_square_p_value(statistic, degrees_of_freedom),
+    }
+}
+
+/// Same as `chi_square` but first pools consecutive categories until each of them is expected to
+/// contain at least `min_expected` observations, as the test is unreliable for rare categories.
+pub fn chi_square_pooled(observed: &[u64], probabilities: &[f64], min_expected: f64) -> ChiSquare {
+    assert_eq!(observed.len(), probabilities.len());
+    let total: u64 = observed.iter().sum();
+    let mut pooled_observed = vec![];
+    let mut pooled_probabilities = vec![];
+    let (mut o, mut p) = (0, 0.);
+    for (&observed, &probability) in observed.iter().zip(probabilities) {
+        o += observed;
+        p += probability;
+        if p * total as f64 >= min_expected {
+            pooled_observed.push(o);
+            pooled_probabilities.push(p);
+            o = 0;
+            p = 0.;
+        }
+    }
+    // leftover goes with the last category
+    if p > 0. || o > 0 {
+        match pooled_observed.last_mut() {
+            Some(last) => {
+                *last += o;
+                *pooled_probabilities.last_mut().unwrap() += p;
+            }
+            None => {
+                pooled_observed.push(o);
+                pooled_probabilities.push(p);
+            }
+        }
+    }
+    if pooled_observed.len() < 2 {
+        // not enough observations to tell anything
+        return ChiSquare {
+            statistic: 0.,
+            degrees_of_freedom: 0,
+            p_value: 1.,
+        };
+    }
+    chi_square(&pooled_observed, &pooled_probabilities)
+}
+
+/// Probability that a chi-square distributed variable with `degrees_of_freedom` is at least
+/// `statistic`.
+pub fn chi_square_p_value(statistic: f64, degrees_of_freedom: usize) -> f64 {
+    if statistic <= 0. {
+        return 1.;
+    }
+    gamma_q(degrees_of_freedom as f64 / 2., statistic / 2.)
+}
+
+/// Probability that a standard normal variable is at least `|z|` in absolute value.
+pub fn normal_two_tailed_p_value(z: f64) -> f64 {
+    // P(|Z| >= z) = P(chi2(1) >= z^2)
+    chi_square_p_value(z * z, 1)
+}
+
+// Lanczos approximation of ln(gamma(x))
+fn ln_gamma(x: f64) -> f64 {
+    let coefficients = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let (series, _) = coefficients
+        .iter()
+        .fold((1.000_000_000_190_015, x), |(acc, y), c| {
+            (acc + c / (y + 1.), y + 1.)
+        });
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+// Regularized upper incomplete gamma function Q(a, x), see Numerical Recipes 6.2
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x < a + 1. {
+        1. - gamma_p_series(a, x)
+    } else {
+        gamma_q_continued_fraction(a, x)
+    }
+}
+
+fn gamma_p_series(a: f64, x: f64) -> f64 {
+    let mut ap = a;
+    let mut del = 1. / a;
+    let mut sum = del;
+    for _ in 0..1000 {
+        ap += 1.;
+        del *= x / ap;
+        sum += del;
+        if del.abs() < sum.abs() * 1e-15 {
+            break;
+        }
+    }
+    sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+}
+
+fn gamma_q_continued_fraction(a: f64, x: f64) -> f64 {
+    let tiny = 1e-300;
+    let mut b = x + 1. - a;
+    let mut c = 1. / tiny;
+    let mut d = 1. / b;
+    let mut h = d;
+    for i in 1..1000 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1. / d;
+        let del = d * c;
+        h *= del;
+        if (del - 1.).abs() < 1e-15 {
+            break;
+        }
+    }
+    (-x + a * x.ln() - ln_gamma(a)).exp() * h
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_chi_square_p_value() {
+        // critical values from chi-square tables
+        assert_close(chi_square_p_value(3.841, 1), 0.05);
+        assert_close(chi_square_p_value(6.635, 1), 0.01);
+        assert_close(chi_square_p_value(18.307, 10), 0.05);
+        assert_close(chi_square_p_value(30.144, 19), 0.05);
+        assert_close(chi_square_p_value(124.342, 100), 0.05);
+        assert_eq!(chi_square_p_value(0., 3), 1.);
+    }
+
+    #[test]
+    fn test_chi_square() {
+        let res = chi_square(&[10, 20, 30], &[1. / 6., 2. / 6., 3. / 6.]);
+        assert_eq!(res.statistic, 0.);
+        assert_eq!(res.degrees_of_freedom, 2);
+        assert_eq!(res.p_value, 1.);
+
+        let res = chi_square(&[90, 10], &[0.5, 0.5]);
+        assert_close(res.statistic, 64.);
+        assert!(res.p_value < 1e-10);
+    }
+
+    #[test]
+    fn test_chi_square_pooled() {
+        // first two and last two categories are pooled
+        let res = chi_square_pooled(
+            &[1, 2, 50, 45, 1, 1],
+            &[0.01, 0.02, 0.47, 0.47, 0.02, 0.01],
+            5.,
+        );
+        assert_eq!(res, chi_square(&[53, 47], &[0.5, 0.5]));
+        let res = chi_square_pooled(&[1, 1], &[0.5, 0.5], 5.);
+        assert_eq!(res.p_value, 1.);
+    }
+
+    #[test]
+    fn test_normal_two_tailed_p_value() {
+        assert_close(normal_two_tailed_p_value(1.959_964), 0.05);
+        assert_close(normal_two_tailed_p_value(-2.575_829), 0.01);
+    }
+}
@@ -0,0 +1,236 @@
+//! # per-game outcome distribution tests
+//!
+//! Simulates consecutive nonces of a seed pair and compares the empirical distribution of a
+//! game's outcome with its exact theoretical distribution using a chi-square test.
+
+use super::rng::SIGNIFICANCE_LEVEL;
+use super::{chi_square_pooled, ChiSquare};
+use crate::games::{baccarat, keno, plinko, roulette, wheel};
+use crate::rng::ProvablyFairConfig;
+use serde::Serialize;
+use std::fmt;
+
+/// Categories expected to contain fewer observations than this are pooled together.
+pub const MIN_EXPECTED: f64 = 5.;
+
+/// A game and the options that determine the distribution of its outcome.
+#[derive(Debug, Copy, Clone)]
+pub enum Game {
+    /// slot the ball lands in
+    Plinko(plinko::Opts),
+    /// segment the wheel stops at
+    Wheel(wheel::Opts),
+    /// pocket the ball lands in
+    Roulette,
+    /// number of hits when picking squares 1 to `picks`
+    Keno { picks: u8 },
+    /// banker, player or tie
+    Baccarat,
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Game::Plinko(opts) => write!(f, "plinko (rows: {})", opts.rows()),
+            Game::Wheel(opts) => write!(f, "wheel (segments: {})", opts.segments()),
+            Game::Roulette => write!(f, "roulette"),
+            Game::Keno { picks } => write!(f, "keno (picks: {})", picks),
+            Game::Baccarat => write!(f, "baccarat"),
+        }
+    }
+}
+
+impl Game {
+    /// Every configuration of `game` that changes its outcome distribution.
+    pub fn all_configs(game: &str) -> Vec<Game> {
+        match game {
+            "plinko" => (8..=16)
+                .map(|rows| Game::Plinko(plinko::Opts::new(rows, plinko::Risk::Low)))
+                .collect(),
+            "wheel" => [10, 20, 30, 40, 50]
+                .iter()
+                .map(|&segments| Game::Wheel(wheel::Opts::new(segments, wheel::Risk::Low)))
+                .collect(),
+            "roulette" => vec![Game::Roulette],
+            "keno" => (1..=10).map(|picks| Game::Keno { picks }).collect(),
+            "baccarat" => vec![Game::Baccarat],
+            _ => panic!("no outcome distribution for game {}", game),
+        }
+    }
+
+    // label and exact probability of every possible outcome
+    fn theoretical_distribution(&self) -> Vec<(String, f64)> {
+        match self {
+            Game::Plinko(opts) => {
+                let rows = opts.rows() as usize;
+                (0..=rows)
+                    .map(|idx| {
+                        let label = format!("slot {}", idx + 1);
+                        (label, plinko::slot_probability(rows, idx))
+                    })
+                    .collect()
+            }
+            Game::Wheel(opts) => {
+                let segments = opts.segments() as usize;
+                (0..segments)
+                    .map(|idx| (format!("segment {}", idx), 1. / segments as f64))
+                    .collect()
+            }
+            Game::Roulette => (0..37)
+                .map(|pocket| (format!("pocket {}", pocket), 1. / 37.))
+                .collect(),
+            Game::Keno { picks } => (0..=*picks as usize)
+                .map(|hits| {
+                    let label = format!("{} hits", hits);
+                    (label, keno::hits_probability(*picks as usize, hits))
+                })
+                .collect(),
+            Game::Baccarat => baccarat::outcome_probabilities()
+                .iter()
+                .map(|(outcome, probability)| (outcome.to_string(), *probability))
+                .collect(),
+        }
+    }
+
+    // index of the outcome in `theoretical_distribution`
+    fn outcome(&self, config: ProvablyFairConfig) -> usize {
+        match self {
+            Game::Plinko(opts) => plinko::simulate(config, Some(*opts)).index,
+            Game::Wheel(opts) => wheel::simulate(config, Some(*opts)).index,
+            Game::Roulette => roulette::simulate(config).pocket as usize,
+            Game::Keno { picks } => keno::simulate(config)
+                .squares
+                .iter()
+                .filter(|&&square| square <= *picks)
+                .count(),
+            Game::Baccarat => match baccarat::simulate(config).outcome {
+                baccarat::Outcome::Banker => 0,
+                baccarat::Outcome::Player => 1,
+                baccarat::Outcome::Tie => 2,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Category {
+    pub label: String,
+    pub observed: u64,
+    pub expected: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub game: String,
+    pub nonces: usize,
+    pub categories: Vec<Category>,
+    pub chi_square: ChiSquare,
+}
+
+impl Report {
+    pub fn is_suspicious(&self) -> bool {
+        self.chi_square.p_value < SIGNIFICANCE_LEVEL
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Game: {} over {} nonces\n", self.game, self.nonces)?;
+        writeln!(f, "{:<16}{:>10}{:>14}", "Outcome", "Observed", "Expected")?;
+        for c in &self.categories {
+            writeln!(f, "{:<16}{:>10}{:>14.2}", c.label, c.observed, c.expected)?;
+        }
+        write!(
+            f,
+            "\n{} {}",
+            self.chi_square,
+            if self.is_suspicious() {
+                "SUSPICIOUS"
+            } else {
+                "ok"
+            }
+        )
+    }
+}
+
+/// Simulates `game` for `nonces` consecutive nonces starting at `nonce` and compares the outcomes
+/// with the theoretical distribution.
+///
+/// # Example
+///
+/// ```
+/// use fair::stats::games::{run, Game};
+///
+/// let report = run(Game::Roulette, "some client seed", "some server seed", 1, 1000);
+/// println!("{}", report);
+/// ```
+///
+pub fn run(game: Game, client_seed: &str, server_seed: &str, nonce: u64, nonces: usize) -> Report {
+    let distribution = game.theoretical_distribution();
+    let mut observed = vec![0; distribution.len()];
+    for n in nonce..nonce + nonces as u64 {
+        let config = ProvablyFairConfig::new(client_seed, server_seed, n);
+        observed[game.outcome(config)] += 1;
+    }
+
+    let probabilities: Vec<f64> = distribution.iter().map(|(_, p)| *p).collect();
+    let chi_square = chi_square_pooled(&observed, &probabilities, MIN_EXPECTED);
+    let categories = distribution
+        .into_iter()
+        .zip(observed)
+        .map(|((label, probability), observed)| Category {
+            label,
+            observed,
+            expected: probability * nonces as f64,
+        })
+        .collect();
+
+    Report {
+        game: game.to_string(),
+        nonces,
+        categories,
+        chi_square,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_theoretical_distributions_sum_to_one() {
+        for game in &["plinko", "wheel", "roulette", "keno", "baccarat"] {
+            for config in Game::all_configs(game) {
+                let total: f64 = config
+                    .theoretical_distribution()
+                    .iter()
+                    .map(|(_, p)| p)
+                    .sum();
+                assert!((total - 1.).abs() < 1e-9, "{}", config);
+            }
+        }
+    }
+
+    #[test]
+    fn test_games_follow_theory() {
+        for game in &["plinko", "wheel", "roulette", "keno", "baccarat"] {
+            for config in Game::all_configs(game) {
+                let report = run(config, "client seed", "server seed", 1, 2000);
+                assert_eq!(
+                    report.categories.iter().map(|c| c.observed).sum::<u64>(),
+                    2000
+                );
+                assert!(!report.is_suspicious(), "{}", report);
+            }
+        }
+    }
+
+    #[test]
+    fn test_outcome() {
+        let config = ProvablyFairConfig::new("client seed", "server seed", 1);
+        assert_eq!(Game::Roulette.outcome(config), 27);
+        // squares drawn are [30, 26, 10, 37, 22, 35, 25, 24, 39, 4]
+        let config = ProvablyFairConfig::new("client seed", "server seed", 1);
+        assert_eq!(Game::Keno { picks: 10 }.outcome(config), 2);
+    }
+}
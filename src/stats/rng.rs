@@ -0,0 +1,331 @@
+//! # statistical tests over RNG output
+//!
+//! Draws bytes and floats from `ProvablyFairRNG` and runs classic randomness tests on them
+//! (chi-square uniformity, serial correlation, runs and gap tests). Every test reports a p-value:
+//! the probability of observing a result at least as extreme if the output was truly random.
+
+use super::{chi_square, normal_two_tailed_p_value};
+use crate::rng::ProvablyFairRNG;
+use serde::Serialize;
+use std::fmt;
+
+/// Tests with a p-value below this are reported as suspicious.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.001;
+
+/// Ranges used by the CLI when none are given: plinko directions (2), diamond poker gems (7),
+/// roulette pockets (37) and cards (52).
+pub const DEFAULT_RANGES: [usize; 4] = [2, 7, 37, 52];
+
+/// Checks that floats can be mapped to `range` outcomes: at least 2 and, since each outcome gets
+/// a counter, no more than the number of `samples`.
+pub fn validate_range(range: usize, samples: usize) -> Result<(), String> {
+    if range < 2 {
+        Err(format!("range {} must be greater or equal to 2", range))
+    } else if range > samples {
+        Err(format!(
+            "range {} must be at most the number of samples ({})",
+            range, samples
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Source of RNG output to test.
+pub struct Sampler<'a> {
+    client_seed: &'a str,
+    server_seed: &'a str,
+    nonce: u64,
+    across_nonces: bool,
+}
+
+impl<'a> Sampler<'a> {
+    /// Draws consecutive values from the RNG of a single nonce.
+    pub fn new(client_seed: &'a str, server_seed: &'a str, nonce: u64) -> Sampler<'a> {
+        Sampler {
+            client_seed,
+            server_seed,
+            nonce,
+            across_nonces: false,
+        }
+    }
+
+    /// Draws the first value of consecutive nonces starting at `nonce` instead, which is what
+    /// most games actually use.
+    pub fn across_nonces(self) -> Sampler<'a> {
+        Sampler {
+            across_nonces: true,
+            ..self
+        }
+    }
+
+    fn sample<T>(&self, count: usize) -> Vec<T>
+    where
+        ProvablyFairRNG<T>: Iterator<Item = T>,
+    {
+        if self.across_nonces {
+            (0..count as u64)
+                .map(|i| {
+                    let mut rng: ProvablyFairRNG<T> =
+                        ProvablyFairRNG::new(self.client_seed, self.server_seed, self.nonce + i);
+                    rng.next().unwrap()
+                })
+                .collect()
+        } else {
+            let rng: ProvablyFairRNG<T> =
+                ProvablyFairRNG::new(self.client_seed, self.server_seed, self.nonce);
+            rng.take(count).collect()
+        }
+    }
+
+    pub fn bytes(&self, count: usize) -> Vec<u8> {
+        self.sample(count)
+    }
+
+    pub fn floats(&self, count: usize) -> Vec<f64> {
+        self.sample(count)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestResult {
+    pub name: String,
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+impl TestResult {
+    pub fn is_suspicious(&self) -> bool {
+        self.p_value < SIGNIFICANCE_LEVEL
+    }
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<32}{:>14.4}{:>10.4}  {}",
+            self.name,
+            self.statistic,
+            self.p_value,
+            if self.is_suspicious() {
+                "SUSPICIOUS"
+            } else {
+                "ok"
+            }
+        )
+    }
+}
+
+/// Chi-square test that every byte value is equally likely.
+pub fn byte_uniformity(bytes: &[u8]) -> TestResult {
+    let mut observed = vec![0; 256];
+    for &b in bytes {
+        observed[b as usize] += 1;
+    }
+    let res = chi_square(&observed, &[1. / 256.; 256]);
+    TestResult {
+        name: "byte uniformity (chi2)".to_string(),
+        statistic: res.statistic,
+        p_value: res.p_value,
+    }
+}
+
+/// Chi-square test that `(float * n) as usize`, the mapping used by games to pick one of `n`
+/// outcomes, is uniform.
+pub fn range_uniformity(floats: &[f64], n: usize) -> TestResult {
+    let mut observed = vec![0; n];
+    for &f in floats {
+        observed[(f * n as f64) as usize] += 1;
+    }
+    let res = chi_square(&observed, &vec![1. / n as f64; n]);
+    TestResult {
+        name: format!("float * {} uniformity (chi2)", n),
+        statistic: res.statistic,
+        p_value: res.p_value,
+    }
+}
+
+/// Lag-1 serial correlation of the floats. Under independence, `r * sqrt(n)` is approximately
+/// standard normal.
+pub fn serial_correlation(floats: &[f64]) -> TestResult {
+    let n = floats.len() as f64;
+    let mean = floats.iter().sum::<f64>() / n;
+    let variance: f64 = floats.iter().map(|f| (f - mean).powi(2)).sum();
+    let covariance: f64 = floats
+        .windows(2)
+        .map(|w| (w[0] - mean) * (w[1] - mean))
+        .sum();
+    let r = covariance / variance;
+    TestResult {
+        name: "serial correlation".to_string(),
+        statistic: r,
+        p_value: normal_two_tailed_p_value(r * n.sqrt()),
+    }
+}
+
+/// Wald-Wolfowitz runs test: counts runs of consecutive floats above/below 0.5.
+pub fn runs(floats: &[f64]) -> TestResult {
+    let above: Vec<bool> = floats.iter().map(|&f| f >= 0.5).collect();
+    let n1 = above.iter().filter(|&&a| a).count() as f64;
+    let n2 = above.len() as f64 - n1;
+    let n = n1 + n2;
+    let runs = 1 + above.windows(2).filter(|w| w[0] != w[1]).count();
+
+    let mean = 2. * n1 * n2 / n + 1.;
+    let variance = 2. * n1 * n2 * (2. * n1 * n2 - n) / (n * n * (n - 1.));
+    let z = (runs as f64 - mean) / variance.sqrt();
+    TestResult {
+        name: "runs above/below 0.5".to_string(),
+        statistic: z,
+        p_value: normal_two_tailed_p_value(z),
+    }
+}
+
+/// Knuth's gap test: lengths of the gaps between floats falling in [0, 0.25) should be
+/// geometrically distributed.
+pub fn gap(floats: &[f64]) -> TestResult {
+    let p = 0.25;
+    let max_gap = 15;
+    let mut observed = vec![0; max_gap + 1];
+    let mut current = 0;
+    for &f in floats {
+        if f < p {
+            observed[current.min(max_gap)] += 1;
+            current = 0;
+        } else {
+            current += 1;
+        }
+    }
+    let mut probabilities: Vec<f64> = (0..max_gap).map(|r| p * (1. - p).powi(r as i32)).collect();
+    probabilities.push((1. - p).powi(max_gap as i32));
+    let res = chi_square(&observed, &probabilities);
+    TestResult {
+        name: "gap test [0, 0.25) (chi2)".to_string(),
+        statistic: res.statistic,
+        p_value: res.p_value,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub samples: usize,
+    pub across_nonces: bool,
+    pub tests: Vec<TestResult>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Samples: {} ({})\n",
+            self.samples,
+            if self.across_nonces {
+                "first value of consecutive nonces"
+            } else {
+                "consecutive values of a single nonce"
+            }
+        )?;
+        writeln!(f, "{:<32}{:>14}{:>10}", "Test", "Statistic", "p-value")?;
+        for test in &self.tests {
+            writeln!(f, "{}", test)?;
+        }
+        let suspicious = self.tests.iter().filter(|t| t.is_suspicious()).count();
+        write!(
+            f,
+            "\n{} of {} tests below the {} significance level",
+            suspicious,
+            self.tests.len(),
+            SIGNIFICANCE_LEVEL
+        )
+    }
+}
+
+/// Runs every test on `samples` bytes and `samples` floats, checking the index mapping for each
+/// of `ranges`.
+///
+/// # Example
+///
+/// ```
+/// use fair::stats::rng::{run, Sampler};
+///
+/// let sampler = Sampler::new("some client seed", "some server seed", 1);
+/// let report = run(&sampler, 10_000, &[2, 52]);
+/// println!("{}", report);
+/// ```
+///
+pub fn run(sampler: &Sampler, samples: usize, ranges: &[usize]) -> Report {
+    let bytes = sampler.bytes(samples);
+    let floats = sampler.floats(samples);
+
+    let mut tests = vec![byte_uniformity(&bytes)];
+    tests.extend(ranges.iter().map(|&n| range_uniformity(&floats, n)));
+    tests.push(serial_correlation(&floats));
+    tests.push(runs(&floats));
+    tests.push(gap(&floats));
+
+    Report {
+        samples,
+        across_nonces: sampler.across_nonces,
+        tests,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_range() {
+        assert!(validate_range(2, 100).is_ok());
+        assert!(validate_range(100, 100).is_ok());
+        assert!(validate_range(1, 100).is_err());
+        assert_eq!(
+            validate_range(1_000_000, 100),
+            Err("range 1000000 must be at most the number of samples (100)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_sampler() {
+        let sampler = Sampler::new("some client seed", "some server seed", 1);
+        assert_eq!(sampler.bytes(3), vec![151, 136, 121]);
+        assert_eq!(
+            sampler.floats(2),
+            vec![0.5919261889066547, 0.81884371698834]
+        );
+        let sampler = sampler.across_nonces();
+        assert_eq!(sampler.bytes(1), vec![151]);
+        assert_eq!(sampler.floats(1), vec![0.5919261889066547]);
+    }
+
+    #[test]
+    fn test_rng_passes() {
+        let sampler = Sampler::new("some client seed", "some server seed", 1);
+        let report = run(&sampler, 20_000, &DEFAULT_RANGES);
+        for test in &report.tests {
+            assert!(!test.is_suspicious(), "{}", test);
+        }
+
+        let sampler = sampler.across_nonces();
+        let report = run(&sampler, 5_000, &DEFAULT_RANGES);
+        for test in &report.tests {
+            assert!(!test.is_suspicious(), "{}", test);
+        }
+    }
+
+    #[test]
+    fn test_biased_input_fails() {
+        let bytes: Vec<u8> = (0..10_000).map(|i| (i % 200) as u8).collect();
+        assert!(byte_uniformity(&bytes).is_suspicious());
+
+        let floats: Vec<f64> = (0..10_000).map(|i| (i as f64 / 10_000.).powi(2)).collect();
+        assert!(range_uniformity(&floats, 2).is_suspicious());
+        // sorted input is strongly correlated and has only 2 runs
+        assert!(serial_correlation(&floats).is_suspicious());
+        assert!(runs(&floats).is_suspicious());
+
+        let alternating: Vec<f64> = (0..10_000).map(|i| (i % 2) as f64 * 0.9).collect();
+        assert!(gap(&alternating).is_suspicious());
+    }
+}
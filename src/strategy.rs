@@ -0,0 +1,647 @@
+//! # betting strategy simulator
+//!
+//! Replays a betting strategy (Martingale, D'Alembert, Paroli, fixed fraction or custom on-win /
+//! on-loss rules) over consecutive nonces of a seed pair and tracks the bankroll.
+
+#[cfg(feature = "script")]
+pub mod script;
+
+use crate::games::{dice, limbo, plinko, roulette, wheel};
+use crate::rng::ProvablyFairConfig;
+use serde::Serialize;
+use std::fmt;
+
+/// Red pockets of a european roulette wheel.
+pub const ROULETTE_RED: [u8; 18] = [
+    1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36,
+];
+
+/// What is being wagered on at each bet.
+#[derive(Debug, Clone)]
+pub enum Bet {
+    /// roll over (or under) `target`, pays `99 / win chance`
+    Dice {
+        target: f64,
+        over: bool,
+    },
+    /// outcome at or above `target`, pays `target`
+    Limbo {
+        target: f64,
+    },
+    /// ball lands in one of `pockets`, pays `36 / pockets.len()`
+    Roulette {
+        pockets: Vec<u8>,
+    },
+    Plinko(plinko::Opts),
+    Wheel(wheel::Opts),
+}
+
+impl Bet {
+    /// Payout multiplier of the bet for a given nonce (0 when lost).
+    pub fn multiplier(&self, config: ProvablyFairConfig) -> f64 {
+        match self {
+            Bet::Dice { target, over } => {
+                let outcome = dice::simulate(config).outcome;
+                let (won, chance) = if *over {
+                    (outcome > *target, 100. - target)
+                } else {
+                    (outcome < *target, *target)
+                };
+                if won {
+                    99. / chance
+                } else {
+                    0.
+                }
+            }
+            Bet::Limbo { target } => {
+                if limbo::simulate(config).outcome >= *target {
+                    *target
+                } else {
+                    0.
+                }
+            }
+            Bet::Roulette { pockets } => {
+                let pocket = roulette::simulate(config).pocket;
+                if pockets.contains(&pocket) {
+                    36. / pockets.len() as f64
+                } else {
+                    0.
+                }
+            }
+            Bet::Plinko(opts) => plinko::simulate(config, Some(*opts)).payout,
+            Bet::Wheel(opts) => wheel::simulate(config, Some(*opts)).payout,
+        }
+    }
+}
+
+/// How the bet amount changes after a win or a loss.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Rule {
+    /// go back to the base bet
+    Reset,
+    /// bet the same amount again
+    Keep,
+    /// multiply the bet by the given factor
+    Multiply(f64),
+    /// add the given amount (can be negative) to the bet, never going below the base bet
+    Add(f64),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Strategy {
+    pub base_bet: f64,
+    pub on_win: Rule,
+    pub on_loss: Rule,
+    /// go back to the base bet after that many consecutive wins
+    pub max_win_streak: Option<u32>,
+    /// when set, the base bet is this fraction of the current bankroll
+    pub bankroll_fraction: Option<f64>,
+    /// stop once this amount has been lost
+    pub stop_loss: Option<f64>,
+    /// stop once this amount has been won
+    pub take_profit: Option<f64>,
+}
+
+impl Strategy {
+    /// Always bets `base_bet`.
+    pub fn flat(base_bet: f64) -> Strategy {
+        Strategy {
+            base_bet,
+            on_win: Rule::Keep,
+            on_loss: Rule::Keep,
+            max_win_streak: None,
+            bankroll_fraction: None,
+            stop_loss: None,
+            take_profit: None,
+        }
+    }
+    /// Doubles the bet after each loss, back to `base_bet` after a win.
+    pub fn martingale(base_bet: f64) -> Strategy {
+        Strategy {
+            on_win: Rule::Reset,
+            on_loss: Rule::Multiply(2.),
+            ..Self::flat(base_bet)
+        }
+    }
+    /// Adds `base_bet` after each loss, removes it after each win.
+    pub fn d_alembert(base_bet: f64) -> Strategy {
+        Strategy {
+            on_win: Rule::Add(-base_bet),
+            on_loss: Rule::Add(base_bet),
+            ..Self::flat(base_bet)
+        }
+    }
+    /// Doubles the bet after each win, back to `base_bet` after a loss or 3 consecutive wins.
+    pub fn paroli(base_bet: f64) -> Strategy {
+        Strategy {
+            on_win: Rule::Multiply(2.),
+            on_loss: Rule::Reset,
+            max_win_streak: Some(3),
+            ..Self::flat(base_bet)
+        }
+    }
+    /// Always bets `fraction` of the current bankroll.
+    pub fn fixed_fraction(fraction: f64) -> Strategy {
+        assert!(fraction > 0. && fraction <= 1.);
+        Strategy {
+            on_win: Rule::Reset,
+            on_loss: Rule::Reset,
+            bankroll_fraction: Some(fraction),
+            ..Self::flat(0.)
+        }
+    }
+    pub fn with_stop_loss(self, stop_loss: f64) -> Strategy {
+        Strategy {
+            stop_loss: Some(stop_loss),
+            ..self
+        }
+    }
+    pub fn with_take_profit(self, take_profit: f64) -> Strategy {
+        Strategy {
+            take_profit: Some(take_profit),
+            ..self
+        }
+    }
+
+    fn base_bet(&self, bankroll: f64) -> f64 {
+        match self.bankroll_fraction {
+            Some(fraction) => bankroll * fraction,
+            None => self.base_bet,
+        }
+    }
+
+    fn reached_max_win_streak(&self, win_streak: u32) -> bool {
+        self.max_win_streak.is_some_and(|max| win_streak >= max)
+    }
+
+    fn next_bet(&self, bet: f64, won: bool, win_streak: u32, bankroll: f64) -> f64 {
+        let base_bet = self.base_bet(bankroll);
+        if won && self.reached_max_win_streak(win_streak) {
+            return base_bet;
+        }
+        match if won { self.on_win } else { self.on_loss } {
+            Rule::Reset => base_bet,
+            Rule::Keep => bet,
+            Rule::Multiply(factor) => bet * factor,
+            Rule::Add(amount) => (bet + amount).max(base_bet),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub enum StopReason {
+    /// all bets were placed
+    Completed,
+    /// the bankroll can't cover the next bet
+    Ruin,
+    StopLoss,
+    TakeProfit,
+    /// a strategy script asked to stop
+    Script,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            StopReason::Completed => "completed",
+            StopReason::Ruin => "ruin",
+            StopReason::StopLoss => "stop loss",
+            StopReason::TakeProfit => "take profit",
+            StopReason::Script => "stopped by script",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BetRecord {
+    pub nonce: u64,
+    pub amount: f64,
+    pub multiplier: f64,
+    /// bankroll after the bet
+    pub bankroll: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Run {
+    pub initial_bankroll: f64,
+    pub bets: Vec<BetRecord>,
+    pub final_bankroll: f64,
+    /// largest drop of the bankroll from a previous peak
+    pub max_drawdown: f64,
+    pub stop_reason: StopReason,
+}
+
+impl Run {
+    /// Bankroll before the first bet and after each bet.
+    pub fn bankroll_curve(&self) -> Vec<f64> {
+        std::iter::once(self.initial_bankroll)
+            .chain(self.bets.iter().map(|b| b.bankroll))
+            .collect()
+    }
+    pub fn profit(&self) -> f64 {
+        self.final_bankroll - self.initial_bankroll
+    }
+}
+
+impl fmt::Display for Run {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Bets: {}\nFinal bankroll: {:.8}\nProfit: {:.8}\nMax drawdown: {:.8}\nStopped: {}",
+            self.bets.len(),
+            self.final_bankroll,
+            self.profit(),
+            self.max_drawdown,
+            self.stop_reason
+        )
+    }
+}
+
+/// Plays `strategy` with `bankroll` for at most `max_bets` consecutive nonces starting at
+/// `nonce`.
+///
+/// # Example
+///
+/// ```
+/// use fair::strategy::{run, Bet, Strategy};
+///
+/// let bet = Bet::Dice { target: 50.5, over: true };
+/// let strategy = Strategy::martingale(1.).with_take_profit(10.);
+/// let res = run(&bet, &strategy, "some client seed", "some server seed", 1, 100., 1000);
+/// println!("{}", res);
+/// ```
+///
+pub fn run(
+    bet: &Bet,
+    strategy: &Strategy,
+    client_seed: &str,
+    server_seed: &str,
+    nonce: u64,
+    bankroll: f64,
+    max_bets: usize,
+) -> Run {
+    let initial_bankroll = bankroll;
+    let mut win_streak = 0;
+    play(
+        |config| Ok(bet.multiplier(config)),
+        client_seed,
+        server_seed,
+        nonce,
+        bankroll,
+        max_bets,
+        |last| {
+            let last = match last {
+                Some(last) => last,
+                None => return Ok(strategy.base_bet(initial_bankroll)),
+            };
+            let profit = last.bankroll - initial_bankroll;
+            if strategy.stop_loss.is_some_and(|limit| profit <= -limit) {
+                return Err(StopReason::StopLoss);
+            }
+            if strategy.take_profit.is_some_and(|target| profit >= target) {
+                return Err(StopReason::TakeProfit);
+            }
+
+            // a push is neither a win nor a loss, the bet and the streak are kept
+            if last.multiplier == 1. {
+                return Ok(last.amount);
+            }
+            let won = last.multiplier > 1.;
+            win_streak = if won { win_streak + 1 } else { 0 };
+            let amount = strategy.next_bet(last.amount, won, win_streak, last.bankroll);
+            if won && strategy.reached_max_win_streak(win_streak) {
+                win_streak = 0;
+            }
+            Ok(amount)
+        },
+    )
+}
+
+// Places bets on consecutive nonces. `payout` returns the multiplier of a nonce or why to stop.
+// `next_bet` is given the previous bet (none before the first one) and returns the amount of the
+// next bet or why to stop.
+pub(crate) fn play<M, F>(
+    mut payout: M,
+    client_seed: &str,
+    server_seed: &str,
+    nonce: u64,
+    bankroll: f64,
+    max_bets: usize,
+    mut next_bet: F,
+) -> Run
+where
+    M: FnMut(ProvablyFairConfig) -> Result<f64, StopReason>,
+    F: FnMut(Option<&BetRecord>) -> Result<f64, StopReason>,
+{
+    let initial_bankroll = bankroll;
+    let mut bankroll = bankroll;
+    let mut peak = bankroll;
+    let mut max_drawdown: f64 = 0.;
+    let mut bets: Vec<BetRecord> = Vec::with_capacity(max_bets);
+    let mut stop_reason = StopReason::Completed;
+
+    let mut decision = next_bet(None);
+    for n in nonce..nonce + max_bets as u64 {
+        let amount = match decision {
+            Ok(amount) => amount,
+            Err(reason) => {
+                stop_reason = reason;
+                break;
+            }
+        };
+        if amount > bankroll || amount <= 0. || amount.is_nan() {
+            stop_reason = StopReason::Ruin;
+            break;
+        }
+        let config = ProvablyFairConfig::new(client_seed, server_seed, n);
+        let multiplier = match payout(config) {
+            Ok(multiplier) => multiplier,
+            Err(reason) => {
+                stop_reason = reason;
+                break;
+            }
+        };
+        bankroll += amount * (multiplier - 1.);
+        bets.push(BetRecord {
+            nonce: n,
+            amount,
+            multiplier,
+            bankroll,
+        });
+
+        peak = peak.max(bankroll);
+        max_drawdown = max_drawdown.max(peak - bankroll);
+
+        decision = next_bet(bets.last());
+    }
+    // stop loss or take profit reached on the very last bet
+    if let (StopReason::Completed, Err(reason)) = (stop_reason, decision) {
+        stop_reason = reason;
+    }
+
+    Run {
+        initial_bankroll,
+        bets,
+        final_bankroll: bankroll,
+        max_drawdown,
+        stop_reason,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonteCarlo {
+    pub sessions: usize,
+    pub ruin_probability: f64,
+    pub stop_loss_probability: f64,
+    pub take_profit_probability: f64,
+    pub mean_final_bankroll: f64,
+    pub median_final_bankroll: f64,
+    pub mean_max_drawdown: f64,
+}
+
+impl MonteCarlo {
+    /// Aggregates the outcome of independent sessions.
+    pub fn from_runs(runs: &[Run]) -> MonteCarlo {
+        let sessions = runs.len();
+        assert!(sessions > 0);
+        let frequency = |reason: StopReason| {
+            runs.iter().filter(|r| r.stop_reason == reason).count() as f64 / sessions as f64
+        };
+        let mut final_bankrolls: Vec<f64> = runs.iter().map(|r| r.final_bankroll).collect();
+        final_bankrolls.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let median_final_bankroll = if sessions.is_multiple_of(2) {
+            (final_bankrolls[sessions / 2 - 1] + final_bankrolls[sessions / 2]) / 2.
+        } else {
+            final_bankrolls[sessions / 2]
+        };
+
+        MonteCarlo {
+            sessions,
+            ruin_probability: frequency(StopReason::Ruin),
+            stop_loss_probability: frequency(StopReason::StopLoss),
+            take_profit_probability: frequency(StopReason::TakeProfit),
+            mean_final_bankroll: final_bankrolls.iter().sum::<f64>() / sessions as f64,
+            median_final_bankroll,
+            mean_max_drawdown: runs.iter().map(|r| r.max_drawdown).sum::<f64>() / sessions as f64,
+        }
+    }
+}
+
+impl fmt::Display for MonteCarlo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sessions: {}\nRuin: {:.2}%\nStop loss hit: {:.2}%\nTake profit hit: {:.2}%\n\
+             Mean final bankroll: {:.8}\nMedian final bankroll: {:.8}\nMean max drawdown: {:.8}",
+            self.sessions,
+            self.ruin_probability * 100.,
+            self.stop_loss_probability * 100.,
+            self.take_profit_probability * 100.,
+            self.mean_final_bankroll,
+            self.median_final_bankroll,
+            self.mean_max_drawdown
+        )
+    }
+}
+
+/// Plays `sessions` sessions of at most `max_bets` bets each. Sessions use consecutive,
+/// non-overlapping blocks of nonces starting at `nonce`.
+#[allow(clippy::too_many_arguments)]
+pub fn monte_carlo(
+    bet: &Bet,
+    strategy: &Strategy,
+    client_seed: &str,
+    server_seed: &str,
+    nonce: u64,
+    bankroll: f64,
+    max_bets: usize,
+    sessions: usize,
+) -> MonteCarlo {
+    let runs: Vec<Run> = (0..sessions as u64)
+        .map(|i| {
+            let nonce = nonce + i * max_bets as u64;
+            run(
+                bet,
+                strategy,
+                client_seed,
+                server_seed,
+                nonce,
+                bankroll,
+                max_bets,
+            )
+        })
+        .collect();
+
+    MonteCarlo::from_runs(&runs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bet_multiplier() {
+        // rolls are 74.67 and 53.86
+        let config = || ProvablyFairConfig::new("client seed", "server seed", 1);
+        let bet = Bet::Dice {
+            target: 50.5,
+            over: true,
+        };
+        assert_eq!(bet.multiplier(config()), 2.);
+        let bet = Bet::Dice {
+            target: 50.,
+            over: false,
+        };
+        assert_eq!(bet.multiplier(config()), 0.);
+        // limbo outcome is 1.32
+        assert_eq!(Bet::Limbo { target: 1.3 }.multiplier(config()), 1.3);
+        assert_eq!(Bet::Limbo { target: 2. }.multiplier(config()), 0.);
+        // pocket 27 is red
+        let bet = Bet::Roulette {
+            pockets: ROULETTE_RED.to_vec(),
+        };
+        assert_eq!(bet.multiplier(config()), 2.);
+        let bet = Bet::Plinko(plinko::Opts::new(8, plinko::Risk::Low));
+        assert_eq!(bet.multiplier(config()), 2.1);
+    }
+
+    #[test]
+    fn test_next_bet() {
+        let martingale = Strategy::martingale(1.);
+        assert_eq!(martingale.next_bet(4., false, 0, 100.), 8.);
+        assert_eq!(martingale.next_bet(4., true, 1, 100.), 1.);
+
+        let d_alembert = Strategy::d_alembert(1.);
+        assert_eq!(d_alembert.next_bet(4., false, 0, 100.), 5.);
+        assert_eq!(d_alembert.next_bet(4., true, 1, 100.), 3.);
+        assert_eq!(d_alembert.next_bet(1., true, 1, 100.), 1.);
+
+        let paroli = Strategy::paroli(1.);
+        assert_eq!(paroli.next_bet(2., true, 2, 100.), 4.);
+        assert_eq!(paroli.next_bet(4., true, 3, 100.), 1.);
+        assert_eq!(paroli.next_bet(4., false, 0, 100.), 1.);
+
+        let fixed_fraction = Strategy::fixed_fraction(0.1);
+        assert_eq!(fixed_fraction.base_bet(50.), 5.);
+        assert_eq!(fixed_fraction.next_bet(5., true, 1, 80.), 8.);
+        assert_eq!(fixed_fraction.next_bet(5., false, 0, 40.), 4.);
+    }
+
+    #[test]
+    fn test_run() {
+        let bet = Bet::Dice {
+            target: 50.5,
+            over: true,
+        };
+        let res = run(
+            &bet,
+            &Strategy::flat(1.),
+            "client seed",
+            "server seed",
+            1,
+            1000.,
+            100,
+        );
+        assert_eq!(res.bets.len(), 100);
+        assert_eq!(res.stop_reason, StopReason::Completed);
+        assert_eq!(res.bets[0].bankroll, 1001.);
+        assert_eq!(res.bets[1].bankroll, 1002.);
+        assert_eq!(res.bankroll_curve().len(), 101);
+        let wins = res.bets.iter().filter(|b| b.multiplier > 1.).count() as f64;
+        assert!((res.final_bankroll - (1000. + wins - (100. - wins))).abs() < 1e-9);
+
+        let res = run(
+            &bet,
+            &Strategy::martingale(1.),
+            "client seed",
+            "server seed",
+            1,
+            10.,
+            10_000,
+        );
+        assert_eq!(res.stop_reason, StopReason::Ruin);
+        assert!(res.max_drawdown > 0.);
+
+        let strategy = Strategy::flat(1.).with_take_profit(2.);
+        let res = run(&bet, &strategy, "client seed", "server seed", 1, 10., 100);
+        assert_eq!(res.stop_reason, StopReason::TakeProfit);
+        assert_eq!(res.bets.len(), 2);
+    }
+
+    #[test]
+    fn test_run_push() {
+        // 8 rows at low risk has 1x slots
+        let bet = Bet::Plinko(plinko::Opts::new(8, plinko::Risk::Low));
+        let res = run(
+            &bet,
+            &Strategy::martingale(1.),
+            "client seed",
+            "server seed",
+            1,
+            1000.,
+            100,
+        );
+        let pushes = res.bets.windows(2).filter(|pair| pair[0].multiplier == 1.);
+        assert!(pushes.clone().count() > 0);
+        for pair in pushes {
+            assert_eq!(pair[1].amount, pair[0].amount);
+        }
+
+        // pushes don't break a Paroli win streak either
+        let res = run(
+            &bet,
+            &Strategy::paroli(1.),
+            "client seed",
+            "server seed",
+            1,
+            1000.,
+            100,
+        );
+        let mut win_streak = 0;
+        for pair in res.bets.windows(2) {
+            if pair[0].multiplier > 1. {
+                win_streak = (win_streak + 1) % 3;
+            } else if pair[0].multiplier < 1. {
+                win_streak = 0;
+            }
+            assert_eq!(pair[1].amount, 2f64.powi(win_streak));
+        }
+    }
+
+    #[test]
+    fn test_run_fixed_fraction() {
+        let bet = Bet::Dice {
+            target: 50.5,
+            over: true,
+        };
+        let strategy = Strategy::fixed_fraction(0.1);
+        let res = run(&bet, &strategy, "client seed", "server seed", 1, 100., 20);
+        assert_eq!(res.bets[0].amount, 10.);
+        // the first roll wins, so the second bet is a tenth of the bigger bankroll
+        assert_eq!(res.bets[1].amount, 11.);
+        for pair in res.bets.windows(2) {
+            assert!((pair[1].amount - pair[0].bankroll * 0.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_monte_carlo() {
+        let bet = Bet::Limbo { target: 2. };
+        let strategy = Strategy::martingale(1.).with_stop_loss(50.);
+        let res = monte_carlo(
+            &bet,
+            &strategy,
+            "client seed",
+            "server seed",
+            1,
+            100.,
+            200,
+            20,
+        );
+        assert_eq!(res.sessions, 20);
+        let total = res.ruin_probability + res.stop_loss_probability + res.take_profit_probability;
+        assert!(total <= 1.);
+        assert!(res.median_final_bankroll >= 0.);
+    }
+}
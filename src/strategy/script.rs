@@ -0,0 +1,499 @@
+//! # scripted betting strategies
+//!
+//! Strategies written in [rhai](https://rhai.rs). A script defines a `next_bet(state)` function
+//! called before every bet, which returns the amount to bet or `()` to stop. `state` has the
+//! following properties:
+//!
+//! - `bankroll`, `initial_bankroll` and `profit`
+//! - `nonce` the bet will be played with and `bets` placed so far
+//! - `last`: previous bet (`amount`, `multiplier`, `won`, `nonce` and `result`), `()` before the
+//!   first bet. `result` is the game's `SimulationResult` for that nonce as a map, e.g.
+//!   `state.last.result.outcome` for dice.
+//!
+//! Scripts can bet on any game with `Script::run_game`, in which case they also define a
+//! `payout(result)` function returning the multiplier of the bet for a `SimulationResult`.
+//!
+//! `this` is a map kept between calls that the script can use to store its own state. Scripts
+//! have no access to the file system, clock or network, and are limited in the number of
+//! operations they can run, so replays are deterministic.
+//!
+//! ```text
+//! // martingale that gives up after 5 losses in a row
+//! fn next_bet(state) {
+//!     if state.last == () || state.last.won || this.losses == 5 {
+//!         this.losses = 0;
+//!         return 1.0;
+//!     }
+//!     this.losses += 1;
+//!     state.last.amount * 2.0
+//! }
+//!
+//! // bets on the banker at baccarat
+//! fn payout(result) {
+//!     if result.outcome == "Banker" { 1.95 } else if result.outcome == "Tie" { 1.0 } else { 0.0 }
+//! }
+//! ```
+
+use super::{play, Bet, BetRecord, Run, StopReason};
+use crate::games::{dice, limbo, plinko, roulette, wheel};
+use crate::rng::ProvablyFairConfig;
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+use serde::Serialize;
+use std::cell::RefCell;
+
+/// Maximum number of operations a single `next_bet` or `payout` call may run.
+pub const MAX_OPERATIONS: u64 = 100_000;
+
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    /// stop once this amount has been lost
+    pub stop_loss: Option<f64>,
+    /// stop once this amount has been won
+    pub take_profit: Option<f64>,
+}
+
+impl Script {
+    /// Compiles a strategy script, failing if it doesn't parse or doesn't define `next_bet`.
+    pub fn compile(source: &str) -> Result<Script, String> {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(10_000)
+            .set_max_array_size(10_000)
+            .set_max_map_size(10_000);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        let script = Script {
+            engine,
+            ast,
+            stop_loss: None,
+            take_profit: None,
+        };
+        if !script.defines("next_bet") {
+            return Err("script must define a `next_bet(state)` function".to_string());
+        }
+        Ok(script)
+    }
+    pub fn with_stop_loss(self, stop_loss: f64) -> Script {
+        Script {
+            stop_loss: Some(stop_loss),
+            ..self
+        }
+    }
+    pub fn with_take_profit(self, take_profit: f64) -> Script {
+        Script {
+            take_profit: Some(take_profit),
+            ..self
+        }
+    }
+
+    /// Whether the script defines `payout(result)`, needed by `run_game`.
+    pub fn defines_payout(&self) -> bool {
+        self.defines("payout")
+    }
+
+    fn defines(&self, name: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == 1)
+    }
+
+    /// Plays the script on `bet` with `bankroll` for at most `max_bets` consecutive nonces
+    /// starting at `nonce`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fair::strategy::script::Script;
+    /// use fair::strategy::Bet;
+    ///
+    /// let script = Script::compile("fn next_bet(state) { state.bankroll / 100.0 }").unwrap();
+    /// let bet = Bet::Limbo { target: 2. };
+    /// let res = script.run(&bet, "some client seed", "some server seed", 1, 100., 1000);
+    /// println!("{}", res.unwrap());
+    /// ```
+    ///
+    pub fn run(
+        &self,
+        bet: &Bet,
+        client_seed: &str,
+        server_seed: &str,
+        nonce: u64,
+        bankroll: f64,
+        max_bets: usize,
+    ) -> Result<Run, String> {
+        self.play(
+            |config| {
+                let result = bet_result(bet, config.clone())?;
+                Ok((bet.multiplier(config), result))
+            },
+            client_seed,
+            server_seed,
+            nonce,
+            bankroll,
+            max_bets,
+        )
+    }
+
+    /// Plays the script on any game: `simulate` returns the game's `SimulationResult` for a
+    /// nonce, which the script's `payout(result)` function turns into the multiplier of the bet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fair::games::mines;
+    /// use fair::strategy::script::Script;
+    ///
+    /// let script = Script::compile(
+    ///     "fn next_bet(state) { 1.0 } fn payout(result) { if 0 in result.squares { 0.0 } else { 1.13 } }",
+    /// )
+    /// .unwrap();
+    /// let simulate = |config| mines::simulate(config, 3);
+    /// let res = script.run_game(simulate, "some client seed", "some server seed", 1, 100., 1000);
+    /// println!("{}", res.unwrap());
+    /// ```
+    ///
+    pub fn run_game<T, S>(
+        &self,
+        simulate: S,
+        client_seed: &str,
+        server_seed: &str,
+        nonce: u64,
+        bankroll: f64,
+        max_bets: usize,
+    ) -> Result<Run, String>
+    where
+        T: Serialize,
+        S: Fn(ProvablyFairConfig) -> T,
+    {
+        if !self.defines("payout") {
+            return Err("script must define a `payout(result)` function".to_string());
+        }
+        self.play(
+            |config| {
+                let result = to_dynamic(simulate(config))?;
+                let mut this = Dynamic::UNIT;
+                let multiplier = self.call("payout", &mut this, (result.clone(),))?;
+                Ok((multiplier, result))
+            },
+            client_seed,
+            server_seed,
+            nonce,
+            bankroll,
+            max_bets,
+        )
+    }
+
+    // `outcome` returns the multiplier and result of a nonce
+    fn play<O>(
+        &self,
+        mut outcome: O,
+        client_seed: &str,
+        server_seed: &str,
+        nonce: u64,
+        bankroll: f64,
+        max_bets: usize,
+    ) -> Result<Run, String>
+    where
+        O: FnMut(ProvablyFairConfig) -> Result<(f64, Dynamic), String>,
+    {
+        let mut memory = Dynamic::from_map(Map::new());
+        let error = RefCell::new(None);
+        let last_result = RefCell::new(Dynamic::UNIT);
+        let fail = |e: String| {
+            *error.borrow_mut() = Some(e);
+            StopReason::Script
+        };
+        let run = play(
+            |config| {
+                let (multiplier, result) = outcome(config).map_err(fail)?;
+                *last_result.borrow_mut() = result;
+                Ok(multiplier)
+            },
+            client_seed,
+            server_seed,
+            nonce,
+            bankroll,
+            max_bets,
+            |last| {
+                if let Some(last) = last {
+                    let profit = last.bankroll - bankroll;
+                    if self.stop_loss.is_some_and(|limit| profit <= -limit) {
+                        return Err(StopReason::StopLoss);
+                    }
+                    if self.take_profit.is_some_and(|target| profit >= target) {
+                        return Err(StopReason::TakeProfit);
+                    }
+                }
+                let state = self.state(last, &last_result.borrow(), nonce, bankroll);
+                match self.call_optional("next_bet", &mut memory, (state,)) {
+                    Ok(Some(amount)) => Ok(amount),
+                    Ok(None) => Err(StopReason::Script),
+                    Err(e) => Err(fail(e)),
+                }
+            },
+        );
+        match error.into_inner() {
+            Some(e) => Err(e),
+            None => Ok(run),
+        }
+    }
+
+    // calls a script function returning a number
+    fn call(&self, name: &str, this: &mut Dynamic, args: impl FuncArgs) -> Result<f64, String> {
+        self.call_optional(name, this, args)?
+            .ok_or_else(|| format!("{} returned (), not a number", name))
+    }
+
+    // calls a script function returning a number or `()`
+    fn call_optional(
+        &self,
+        name: &str,
+        this: &mut Dynamic,
+        args: impl FuncArgs,
+    ) -> Result<Option<f64>, String> {
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(this);
+        let res = self
+            .engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, name, args)
+            .map_err(|e| e.to_string())?;
+        if res.is_unit() {
+            return Ok(None);
+        }
+        res.as_float()
+            .or_else(|_| res.as_int().map(|i| i as f64))
+            .map(Some)
+            .map_err(|type_name| format!("{} returned a {}, not a number", name, type_name))
+    }
+
+    fn state(
+        &self,
+        last: Option<&BetRecord>,
+        last_result: &Dynamic,
+        nonce: u64,
+        initial_bankroll: f64,
+    ) -> Map {
+        let mut state = Map::new();
+        let bankroll = last.map_or(initial_bankroll, |last| last.bankroll);
+        let next_nonce = last.map_or(nonce, |last| last.nonce + 1);
+        state.insert("bankroll".into(), bankroll.into());
+        state.insert("initial_bankroll".into(), initial_bankroll.into());
+        state.insert("profit".into(), (bankroll - initial_bankroll).into());
+        state.insert("nonce".into(), (next_nonce as i64).into());
+        state.insert("bets".into(), ((next_nonce - nonce) as i64).into());
+        let last = match last {
+            Some(last) => {
+                let mut record = Map::new();
+                record.insert("amount".into(), last.amount.into());
+                record.insert("multiplier".into(), last.multiplier.into());
+                record.insert("won".into(), (last.multiplier > 1.).into());
+                record.insert("nonce".into(), (last.nonce as i64).into());
+                record.insert("result".into(), last_result.clone());
+                Dynamic::from_map(record)
+            }
+            None => Dynamic::UNIT,
+        };
+        state.insert("last".into(), last);
+        state
+    }
+}
+
+fn to_dynamic<T: Serialize>(result: T) -> Result<Dynamic, String> {
+    rhai::serde::to_dynamic(result).map_err(|e| e.to_string())
+}
+
+// `SimulationResult` of the game `bet` is placed on
+fn bet_result(bet: &Bet, config: ProvablyFairConfig) -> Result<Dynamic, String> {
+    match bet {
+        Bet::Dice { .. } => to_dynamic(dice::simulate(config)),
+        Bet::Limbo { .. } => to_dynamic(limbo::simulate(config)),
+        Bet::Roulette { .. } => to_dynamic(roulette::simulate(config)),
+        Bet::Plinko(opts) => to_dynamic(plinko::simulate(config, Some(*opts))),
+        Bet::Wheel(opts) => to_dynamic(wheel::simulate(config, Some(*opts))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::{run, Strategy};
+
+    const MARTINGALE: &str = r#"
+        fn next_bet(state) {
+            if state.last == () || state.last.won {
+                return 1.0;
+            }
+            state.last.amount * 2.0
+        }
+    "#;
+
+    #[test]
+    fn test_script_matches_builtin_strategy() {
+        let bet = Bet::Dice {
+            target: 50.5,
+            over: true,
+        };
+        let script = Script::compile(MARTINGALE).unwrap();
+        let scripted = script
+            .run(&bet, "client seed", "server seed", 1, 100., 500)
+            .unwrap();
+        let builtin = run(
+            &bet,
+            &Strategy::martingale(1.),
+            "client seed",
+            "server seed",
+            1,
+            100.,
+            500,
+        );
+        assert_eq!(scripted.bankroll_curve(), builtin.bankroll_curve());
+        assert_eq!(scripted.stop_reason, builtin.stop_reason);
+    }
+
+    #[test]
+    fn test_script_state() {
+        // stops after 3 bets using its own memory
+        let script = Script::compile(
+            r#"
+            fn next_bet(state) {
+                if state.last == () { this.count = 0; }
+                if this.count == 3 { return (); }
+                this.count += 1;
+                if state.bets != this.count - 1 { throw "wrong bet count"; }
+                2
+            }
+        "#,
+        )
+        .unwrap();
+        let res = script
+            .run(
+                &Bet::Limbo { target: 2. },
+                "client seed",
+                "server seed",
+                7,
+                10.,
+                100,
+            )
+            .unwrap();
+        assert_eq!(res.bets.len(), 3);
+        assert_eq!(res.bets[0].nonce, 7);
+        assert_eq!(res.bets[0].amount, 2.);
+        assert_eq!(res.stop_reason, StopReason::Script);
+    }
+
+    #[test]
+    fn test_script_errors() {
+        assert!(Script::compile("fn other(state) { 1 }").is_err());
+        assert!(Script::compile("fn next_bet(state) {").is_err());
+
+        let bet = Bet::Limbo { target: 2. };
+        let script = Script::compile(r#"fn next_bet(state) { "a lot" }"#).unwrap();
+        assert!(script
+            .run(&bet, "client seed", "server seed", 1, 10., 10)
+            .is_err());
+        // infinite loops are stopped
+        let script = Script::compile("fn next_bet(state) { loop {} }").unwrap();
+        assert!(script
+            .run(&bet, "client seed", "server seed", 1, 10., 10)
+            .is_err());
+    }
+
+    #[test]
+    fn test_script_game_result() {
+        // bets once on a roll over 50.5, then checks the roll of the result
+        let script = Script::compile(
+            r#"
+            fn next_bet(state) {
+                if state.last == () { return 1; }
+                if state.last.result.outcome != 74.67 { throw "wrong result"; }
+                ()
+            }
+        "#,
+        )
+        .unwrap();
+        let bet = Bet::Dice {
+            target: 50.5,
+            over: true,
+        };
+        let res = script
+            .run(&bet, "client seed", "server seed", 1, 10., 10)
+            .unwrap();
+        assert_eq!(res.bets.len(), 1);
+        assert_eq!(res.stop_reason, StopReason::Script);
+    }
+
+    #[test]
+    fn test_script_any_game() {
+        use crate::games::baccarat;
+
+        let banker = Script::compile(
+            r#"
+            fn next_bet(state) { 1 }
+            fn payout(result) {
+                switch result.outcome { "Banker" => 1.95, "Tie" => 1.0, _ => 0.0 }
+            }
+        "#,
+        )
+        .unwrap();
+        let res = banker
+            .run_game(
+                baccarat::simulate,
+                "client seed",
+                "server seed",
+                1,
+                100.,
+                50,
+            )
+            .unwrap();
+        assert_eq!(res.bets.len(), 50);
+        for bet in &res.bets {
+            let config = ProvablyFairConfig::new("client seed", "server seed", bet.nonce);
+            let expected = match baccarat::simulate(config).outcome {
+                baccarat::Outcome::Banker => 1.95,
+                baccarat::Outcome::Tie => 1.,
+                baccarat::Outcome::Player => 0.,
+            };
+            assert_eq!(bet.multiplier, expected);
+        }
+
+        let script = Script::compile(MARTINGALE).unwrap();
+        assert_eq!(
+            script
+                .run_game(
+                    baccarat::simulate,
+                    "client seed",
+                    "server seed",
+                    1,
+                    100.,
+                    50
+                )
+                .unwrap_err(),
+            "script must define a `payout(result)` function"
+        );
+    }
+
+    #[test]
+    fn test_script_stop_loss_take_profit() {
+        let bet = Bet::Dice {
+            target: 50.5,
+            over: true,
+        };
+        let script = Script::compile(MARTINGALE).unwrap().with_take_profit(2.);
+        let res = script
+            .run(&bet, "client seed", "server seed", 1, 10., 100)
+            .unwrap();
+        assert_eq!(res.stop_reason, StopReason::TakeProfit);
+        assert_eq!(res.bets.len(), 2);
+
+        let script = Script::compile("fn next_bet(state) { 1 }")
+            .unwrap()
+            .with_stop_loss(3.);
+        let res = script
+            .run(&bet, "client seed", "server seed", 1, 100., 1000)
+            .unwrap();
+        assert_eq!(res.stop_reason, StopReason::StopLoss);
+        assert!(res.final_bankroll <= 97.);
+    }
+}
@@ -0,0 +1,301 @@
+//! # fair tui
+//!
+//! Interactive terminal UI of the CLI: seeds are entered once and the nonce, game and options
+//! are changed with the keyboard while the result re-renders. Enabled with the `tui` cargo
+//! feature.
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, style, terminal};
+use fair::games::*;
+use fair::utils;
+use fair::ProvablyFairConfig;
+use std::io::{self, Write};
+
+const GAMES: [&str; 14] = [
+    "baccarat",
+    "blackjack",
+    "diamond_poker",
+    "dice",
+    "dragon_tower",
+    "hilo",
+    "keno",
+    "limbo",
+    "mines",
+    "plinko",
+    "roulette",
+    "slots",
+    "video_poker",
+    "wheel",
+];
+
+const HELP: &str = "up/down: nonce +/-1  pgup/pgdn: nonce +/-100  left/right: game  \
+                    +/-: rows, segments, mines, round or difficulty  r: risk  q: quit";
+
+pub struct App {
+    client_seed: String,
+    server_seed: String,
+    server_seed_hash: String,
+    /// hash the casino committed to, if known
+    expected_hash: Option<String>,
+    nonce: u64,
+    game: usize,
+    rows: u8,
+    segments: usize,
+    mines: u8,
+    round: usize,
+    risk: usize,
+    difficulty: usize,
+}
+
+impl App {
+    pub fn new(
+        client_seed: &str,
+        server_seed: &str,
+        nonce: u64,
+        game: &str,
+        expected_hash: Option<&str>,
+    ) -> App {
+        App {
+            client_seed: client_seed.to_string(),
+            server_seed: server_seed.to_string(),
+            server_seed_hash: utils::hash_server_seed(server_seed),
+            expected_hash: expected_hash.map(|hash| hash.to_lowercase()),
+            nonce,
+            game: GAMES.iter().position(|&g| g == game).unwrap_or(0),
+            rows: 8,
+            segments: 0,
+            mines: 3,
+            round: 0,
+            risk: 0,
+            difficulty: 0,
+        }
+    }
+
+    fn game(&self) -> &'static str {
+        GAMES[self.game]
+    }
+
+    // returns false when the user wants to quit
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up => self.nonce = self.nonce.saturating_add(1),
+            KeyCode::Down => self.nonce = self.nonce.saturating_sub(1),
+            KeyCode::PageUp => self.nonce = self.nonce.saturating_add(100),
+            KeyCode::PageDown => self.nonce = self.nonce.saturating_sub(100),
+            KeyCode::Right => self.game = (self.game + 1) % GAMES.len(),
+            KeyCode::Left => self.game = (self.game + GAMES.len() - 1) % GAMES.len(),
+            KeyCode::Char('r') => self.risk = (self.risk + 1) % Risk::ALL.len(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.change_option(1),
+            KeyCode::Char('-') => self.change_option(-1),
+            _ => {}
+        }
+        true
+    }
+
+    fn change_option(&mut self, delta: i32) {
+        match self.game() {
+            "plinko" => self.rows = (self.rows as i32 + delta).clamp(8, 16) as u8,
+            "wheel" => {
+                let last = wheel::SEGMENTS.len() as i32 - 1;
+                self.segments = (self.segments as i32 + delta).clamp(0, last) as usize;
+            }
+            "mines" => self.mines = (self.mines as i32 + delta).clamp(1, 24) as u8,
+            "slots" => self.round = (self.round as i64 + delta as i64).max(0) as usize,
+            "dragon_tower" => {
+                let last = dragon_tower::Difficulty::ALL.len() as i32 - 1;
+                self.difficulty = (self.difficulty as i32 + delta).clamp(0, last) as usize;
+            }
+            _ => {}
+        }
+    }
+
+    fn options(&self) -> String {
+        match self.game() {
+            "plinko" => format!("rows: {}, risk: {}", self.rows, Risk::ALL[self.risk]),
+            "wheel" => format!(
+                "segments: {}, risk: {}",
+                wheel::SEGMENTS[self.segments],
+                Risk::ALL[self.risk]
+            ),
+            "mines" => format!("mines: {}", self.mines),
+            "slots" => format!("round: {}", self.round),
+            "dragon_tower" => format!(
+                "difficulty: {}",
+                dragon_tower::Difficulty::ALL[self.difficulty]
+            ),
+            _ => "none".to_string(),
+        }
+    }
+
+    fn result(&self) -> String {
+        let config = ProvablyFairConfig::new(&self.client_seed, &self.server_seed, self.nonce);
+        match self.game() {
+            "baccarat" => baccarat::simulate(config).to_string(),
+            "blackjack" => blackjack::simulate(config).to_string(),
+            "diamond_poker" => diamond_poker::simulate(config).to_string(),
+            "dice" => dice::simulate(config).to_string(),
+            "dragon_tower" => {
+                let difficulty = dragon_tower::Difficulty::ALL[self.difficulty];
+                dragon_tower::simulate(config, difficulty).to_string()
+            }
+            "hilo" => hilo::simulate(config).to_string(),
+            "keno" => keno::simulate(config).to_string(),
+            "limbo" => limbo::simulate(config).to_string(),
+            "mines" => mines::simulate(config, self.mines).to_string(),
+            "plinko" => {
+                let opts = plinko::Opts::new(self.rows, Risk::ALL[self.risk]);
+                let result = plinko::simulate(config, Some(opts));
+                format!("{}\n\n{}", draw_plinko_path(&result.path), result)
+            }
+            "roulette" => roulette::simulate(config).to_string(),
+            "slots" => slots::simulate(config, self.round).to_string(),
+            "video_poker" => video_poker::simulate(config).to_string(),
+            "wheel" => {
+                let opts = wheel::Opts::new(wheel::SEGMENTS[self.segments], Risk::ALL[self.risk]);
+                wheel::simulate(config, Some(opts)).to_string()
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn render(&self) -> String {
+        let hash_check = match &self.expected_hash {
+            Some(expected) if *expected == self.server_seed_hash => {
+                "matches the expected hash".to_string()
+            }
+            Some(expected) => format!("!!! DOES NOT MATCH expected hash {} !!!", expected),
+            None => "no expected hash given (--hash)".to_string(),
+        };
+        format!(
+            "Client Seed: {}\nServer Seed: {}\nHashed Server Seed: {} ({})\n\n\
+             Game: < {} >  Options: {}  Nonce: {}\n\n{}\n\n{}",
+            self.client_seed,
+            self.server_seed,
+            self.server_seed_hash,
+            hash_check,
+            self.game(),
+            self.options(),
+            self.nonce,
+            self.result(),
+            HELP
+        )
+    }
+}
+
+// triangle of pins with the position of the ball after each row
+fn draw_plinko_path(path: &[plinko::Direction]) -> String {
+    let rows = path.len();
+    let mut position = 0;
+    path.iter()
+        .enumerate()
+        .map(|(row, &direction)| {
+            if direction == plinko::Direction::Right {
+                position += 1;
+            }
+            let cells: Vec<&str> = (0..=row + 1)
+                .map(|cell| if cell == position { "o" } else { "." })
+                .collect();
+            format!("{}{}", " ".repeat(rows - row), cells.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// restores the terminal even if rendering panics
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+pub fn run(mut app: App) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    loop {
+        queue!(
+            stdout,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0),
+            // raw mode doesn't return to the first column on new lines
+            style::Print(app.render().replace('\n', "\r\n"))
+        )?;
+        stdout.flush()?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if ctrl_c || !app.handle_key(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_handle_key() {
+        let mut app = App::new("client seed", "server seed", 0, "plinko", None);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.nonce, 0);
+        app.handle_key(KeyCode::Up);
+        app.handle_key(KeyCode::PageUp);
+        assert_eq!(app.nonce, 101);
+
+        for _ in 0..10 {
+            app.handle_key(KeyCode::Char('+'));
+        }
+        assert_eq!(app.rows, 16);
+        app.handle_key(KeyCode::Char('r'));
+        assert_eq!(app.options(), "rows: 16, risk: medium");
+
+        app.handle_key(KeyCode::Right);
+        assert_eq!(app.game(), "roulette");
+        app.handle_key(KeyCode::Left);
+        app.handle_key(KeyCode::Left);
+        assert_eq!(app.game(), "mines");
+        for _ in 0..4 {
+            app.handle_key(KeyCode::Left);
+        }
+        assert_eq!(app.game(), "dragon_tower");
+        app.handle_key(KeyCode::Char('+'));
+        assert_eq!(app.options(), "difficulty: medium");
+        assert!(!app.handle_key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_render() {
+        let hash = utils::hash_server_seed("server seed");
+        let app = App::new("client seed", "server seed", 1, "roulette", Some(&hash));
+        let screen = app.render();
+        assert!(screen.contains("matches the expected hash"));
+        assert!(screen.contains(
+            &roulette::simulate(ProvablyFairConfig::new("client seed", "server seed", 1))
+                .to_string()
+        ));
+
+        let app = App::new("client seed", "server seed", 1, "dice", Some("abc"));
+        assert!(app.render().contains("DOES NOT MATCH"));
+    }
+
+    #[test]
+    fn test_draw_plinko_path() {
+        use plinko::Direction::*;
+        assert_eq!(
+            draw_plinko_path(&[Right, Left, Right]),
+            "   . o\n  . o .\n . . o ."
+        );
+    }
+}
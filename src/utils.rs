@@ -5,4 +5,3 @@ use hex;
 pub fn hash_server_seed(server_seed: &str) -> String {
     hex::encode(Sha256::digest(server_seed.as_bytes()))
 }
-
@@ -1,11 +1,20 @@
+//! # wasm
+//!
+//! JS bindings built with wasm-bindgen, see `fair-wasm` for the JS package.
+
 #![allow(dead_code)]
+// TODO: migrate to serde-wasm-bindgen
+#![allow(deprecated)]
 
 use crate::games::*;
-use crate::ProvablyFairConfig;
+use crate::{ProvablyFairConfig, ProvablyFairRNG};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 
 use crate::utils;
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
@@ -25,159 +34,665 @@ pub fn greet() {
 }
 */
 
-fn default_plinko_risk() -> String {
-    "low".to_string()
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_TYPES: &'static str = r#"
+export type Risk = "low" | "medium" | "high";
+
+export interface PlinkoOpts { rows?: number; risk?: Risk; }
+export interface WheelOpts { segments?: 10 | 20 | 30 | 40 | 50; risk?: Risk; }
+export interface MinesOpts { mines?: number; }
+export interface SlotsOpts { round?: number; }
+export type Difficulty = "easy" | "medium" | "hard" | "expert" | "master";
+export interface DragonTowerOpts { difficulty?: Difficulty; }
+/** baccarat, blackjack, hilo and video poker, `decks: 0` draws every card from a full deck */
+export interface CardOpts { decks?: number; }
+export type GameOpts =
+  | PlinkoOpts | WheelOpts | MinesOpts | SlotsOpts | CardOpts | DragonTowerOpts | {};
+
+export type CardRank =
+  | "TWO" | "THREE" | "FOUR" | "FIVE" | "SIX" | "SEVEN" | "EIGHT"
+  | "NINE" | "TEN" | "JACK" | "QUEEN" | "KING" | "ACE";
+export type CardSuite = "DIAMOND" | "CLUB" | "SPADE" | "HEART";
+export interface Card { rank: CardRank; suite: CardSuite; }
+export interface Deck { cards: Card[]; }
+
+export interface BaccaratResult {
+  outcome: "Banker" | "Player" | "Tie";
+  totals: { player: number; banker: number };
+  steps: ["PLAYER" | "BANKER", Card][];
+}
+export interface BlackjackResult { dealer: Deck; player: Deck; deck: Deck; }
+export type Gem = "Green" | "Purple" | "Yellow" | "Red" | "Cyan" | "Orange" | "Blue";
+export interface DiamondPokerHand {
+  gems: Gem[];
+  hand_type: "Nothing" | "Pair" | "TwoPairs" | "ThreeOfAKind" | "FullHouse" | "FourOfAKind";
 }
+export interface DiamondPokerResult {
+  dealer: DiamondPokerHand;
+  player: DiamondPokerHand;
+  outcome: "PlayerWin" | "DealerWin" | "Draw";
+}
+export interface DragonTowerResult {
+  difficulty: "Easy" | "Medium" | "Hard" | "Expert" | "Master";
+  /** tiles of the eggs of each row from the bottom */
+  rows: number[][];
+}
+/** `rows` climbed before cashing out or picking a tile without an egg */
+export interface DragonTowerClimb { picks: number[]; rows: number; busted: boolean; multiplier: number; }
+/** dice roll (0 to 100) or limbo multiplier */
+export interface OutcomeResult { outcome: number; }
+export interface HiloResult { deck: Deck; }
+/** keno's drawn numbers or the squares of mines */
+export interface SquaresResult { squares: number[]; }
+export interface PlinkoResult { payout: number; index: number; path: ("Left" | "Right")[]; }
+export interface RouletteResult { pocket: number; }
+export interface SlotsResult { outcomes: [number, number, number, number, number]; }
+export interface VideoPokerResult { initial_hand: Deck; coming_cards: Deck; }
+export interface WheelResult { payout: number; index: number; }
+export type SimulationResult =
+  | BaccaratResult | BlackjackResult | DiamondPokerResult | DragonTowerResult | OutcomeResult
+  | HiloResult | SquaresResult | PlinkoResult | RouletteResult | SlotsResult | VideoPokerResult | WheelResult;
 
-fn default_plinko_rows() -> i32 {
-    8
+/** Hash chain of a crash casino, missing fields take Stake's values. */
+export interface CrashConfig {
+  hash_chain_tip?: string;
+  block_hash?: string;
+  max_chain_length?: number;
+  house_edge?: number;
 }
+export interface CrashResult { crash_point: number; }
 
-fn default_wheel_segments() -> i32 {
-    10
+/** Thrown by every function of this module. */
+export interface FairError {
+  kind:
+    | "unknown_game"
+    | "unsupported_game"
+    | "invalid_opts"
+    | "invalid_nonce"
+    | "invalid_game_hash"
+    | "invalid_range"
+    | "invalid_position"
+    | "invalid_picks"
+    | "serialization";
+  message: string;
 }
+"#;
 
-fn default_wheel_risk() -> String {
-    "low".to_string()
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "GameOpts")]
+    pub type GameOpts;
+    #[wasm_bindgen(typescript_type = "SimulationResult")]
+    pub type SimulationResult;
+    #[wasm_bindgen(typescript_type = "SimulationResult[]")]
+    pub type SimulationResults;
+    #[wasm_bindgen(typescript_type = "DragonTowerClimb")]
+    pub type DragonTowerClimb;
+    #[wasm_bindgen(typescript_type = "CrashConfig")]
+    pub type CrashConfig;
+    #[wasm_bindgen(typescript_type = "CrashResult")]
+    pub type CrashResult;
+}
+
+/// Error object thrown to JS, see `FairError` in the TypeScript declarations.
+#[derive(Debug, Serialize)]
+struct Error {
+    kind: &'static str,
+    message: String,
+}
+
+impl Error {
+    fn new(kind: &'static str, message: impl Into<String>) -> Error {
+        Error {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(err: Error) -> JsValue {
+        JsValue::from_serde(&err).unwrap_or_else(|_| JsValue::from_str(&err.message))
+    }
+}
+
+fn invalid_opts(message: String) -> Error {
+    Error::new("invalid_opts", message)
 }
 
 #[derive(Deserialize)]
+#[serde(default)]
 struct PlinkoOpts {
-    #[serde(default = "default_plinko_rows")]
     rows: i32,
-    #[serde(default = "default_plinko_risk")]
     risk: String,
 }
 
+impl Default for PlinkoOpts {
+    fn default() -> PlinkoOpts {
+        PlinkoOpts {
+            rows: 8,
+            risk: "low".to_string(),
+        }
+    }
+}
+
 #[derive(Deserialize)]
+#[serde(default)]
 struct WheelOpts {
-    #[serde(default = "default_wheel_segments")]
     segments: i32,
-    #[serde(default = "default_wheel_risk")]
     risk: String,
 }
 
-fn default_mines_mines() -> i32 {
-    3
+impl Default for WheelOpts {
+    fn default() -> WheelOpts {
+        WheelOpts {
+            segments: 10,
+            risk: "low".to_string(),
+        }
+    }
 }
 
 #[derive(Deserialize)]
+#[serde(default)]
 struct MinesOpts {
-    #[serde(default = "default_mines_mines")]
     mines: i32,
 }
 
-fn default_slots_round() -> i32 {
-    0
+impl Default for MinesOpts {
+    fn default() -> MinesOpts {
+        MinesOpts { mines: 3 }
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
+#[serde(default)]
 struct SlotsOpts {
-    #[serde(default = "default_slots_round")]
     round: i32,
 }
 
-#[wasm_bindgen]
-pub fn simulate(
-    game: &str,
-    client_seed: &str,
-    server_seed: &str,
-    nonce: u32,
-    opts: &JsValue,
-) -> JsValue {
-    let config = ProvablyFairConfig::new(client_seed, server_seed, nonce as u64);
-    let result = match game {
-        "baccarat" => {
-            let res = baccarat::simulate(config);
-            JsValue::from_serde(&res).unwrap()
-        }
-        "dice" => {
-            let res = dice::simulate(config);
-            JsValue::from_serde(&res).unwrap()
-        }
-        "limbo" => {
-            let res = limbo::simulate(config);
-            JsValue::from_serde(&res).unwrap()
-        }
-        "hilo" => {
-            let res = hilo::simulate(config);
-            JsValue::from_serde(&res).unwrap()
-        }
-        "blackjack" => {
-            let res = blackjack::simulate(config);
-            JsValue::from_serde(&res).unwrap()
-        }
-        "diamond_poker" => {
-            let res = diamond_poker::simulate(config);
-            JsValue::from_serde(&res).unwrap()
-        }
-        "roulette" => {
-            let res = roulette::simulate(config);
-            JsValue::from_serde(&res).unwrap()
-        }
-        "keno" => {
-            let res = keno::simulate(config);
-            JsValue::from_serde(&res).unwrap()
-        }
-        "plinko" => {
-            let opts: PlinkoOpts = opts.into_serde().unwrap();
-            let rows = opts.rows as u8;
-            let risk = plinko::Risk::from_str(&opts.risk);
-            let res = plinko::simulate(config, Some(plinko::Opts::new(rows, risk)));
-            // format!("Rows: {} Risk: {:?}\n{}", rows, risk, res)
-            JsValue::from_serde(&res).unwrap()
+#[derive(Deserialize)]
+#[serde(default)]
+struct DragonTowerOpts {
+    difficulty: String,
+}
+
+impl Default for DragonTowerOpts {
+    fn default() -> DragonTowerOpts {
+        DragonTowerOpts {
+            difficulty: "easy".to_string(),
         }
-        "mines" => {
-            let opts: MinesOpts = opts.into_serde().unwrap();
-            let mines = opts.mines as u8;
-            let res = mines::simulate(config, mines);
-            // format!("Rows: {} Risk: {:?}\n{}", rows, risk, res)
-            JsValue::from_serde(&res).unwrap()
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct CrashOpts {
+    hash_chain_tip: Option<String>,
+    block_hash: Option<String>,
+    max_chain_length: Option<usize>,
+    house_edge: Option<f64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct CardOpts {
+    decks: i32,
+}
+
+// missing opts take the default values
+fn parse_opts<T: DeserializeOwned + Default>(opts: Option<&JsValue>) -> Result<T, Error> {
+    match opts {
+        Some(opts) if !opts.is_undefined() && !opts.is_null() => {
+            opts.into_serde().map_err(|e| invalid_opts(e.to_string()))
         }
-        "video_poker" => {
-            let res = video_poker::simulate(config);
-            JsValue::from_serde(&res).unwrap()
+        _ => Ok(T::default()),
+    }
+}
+
+fn parse_dealing(opts: &Option<GameOpts>) -> Result<Dealing, Error> {
+    let opts: CardOpts = parse_opts(opts.as_deref())?;
+    // out of range numbers are rejected rather than taken as 0, which is a valid number of decks
+    let decks = u8::try_from(opts.decks).unwrap_or(u8::MAX);
+    Dealing::from_decks(decks).map_err(invalid_opts)
+}
+
+fn parse_difficulty(opts: &Option<GameOpts>) -> Result<dragon_tower::Difficulty, Error> {
+    let opts: DragonTowerOpts = parse_opts(opts.as_deref())?;
+    opts.difficulty.parse().map_err(invalid_opts)
+}
+
+// out of range numbers become 0, which no option accepts
+fn to_u8(n: i32) -> u8 {
+    u8::try_from(n).unwrap_or(0)
+}
+
+fn to_js<T: Serialize, R: JsCast>(res: &T) -> Result<R, Error> {
+    JsValue::from_serde(res)
+        .map(JsCast::unchecked_into)
+        .map_err(|e| Error::new("serialization", e.to_string()))
+}
+
+// game with its options, validated once for all the nonces of a batch
+enum Game {
+    Baccarat(Dealing),
+    Dice,
+    Limbo,
+    Hilo(Dealing),
+    Blackjack(Dealing),
+    DiamondPoker,
+    DragonTower(dragon_tower::Difficulty),
+    Roulette,
+    Keno,
+    Plinko(plinko::Opts),
+    Mines(u8),
+    VideoPoker(Dealing),
+    Wheel(wheel::Opts),
+    Slots(usize),
+}
+
+impl Game {
+    fn parse(game: &str, opts: &Option<GameOpts>) -> Result<Game, Error> {
+        Ok(match game {
+            "baccarat" => Game::Baccarat(parse_dealing(opts)?),
+            "dice" => Game::Dice,
+            "limbo" => Game::Limbo,
+            "hilo" => Game::Hilo(parse_dealing(opts)?),
+            "blackjack" => Game::Blackjack(parse_dealing(opts)?),
+            "diamond_poker" => Game::DiamondPoker,
+            "dragon_tower" => Game::DragonTower(parse_difficulty(opts)?),
+            "roulette" => Game::Roulette,
+            "keno" => Game::Keno,
+            "plinko" => {
+                let opts: PlinkoOpts = parse_opts(opts.as_deref())?;
+                let risk = opts.risk.parse().map_err(invalid_opts)?;
+                let opts = plinko::Opts::default()
+                    .with_rows(to_u8(opts.rows))
+                    .map_err(invalid_opts)?
+                    .with_risk(risk);
+                Game::Plinko(opts)
+            }
+            "mines" => {
+                let opts: MinesOpts = parse_opts(opts.as_deref())?;
+                let mines = to_u8(opts.mines);
+                mines::validate_mines(mines).map_err(invalid_opts)?;
+                Game::Mines(mines)
+            }
+            "video_poker" => Game::VideoPoker(parse_dealing(opts)?),
+            "wheel" => {
+                let opts: WheelOpts = parse_opts(opts.as_deref())?;
+                let risk = opts.risk.parse().map_err(invalid_opts)?;
+                let opts = wheel::Opts::default()
+                    .with_segments(to_u8(opts.segments))
+                    .map_err(invalid_opts)?
+                    .with_risk(risk);
+                Game::Wheel(opts)
+            }
+            "slots" => {
+                let opts: SlotsOpts = parse_opts(opts.as_deref())?;
+                let round = usize::try_from(opts.round)
+                    .map_err(|_| invalid_opts("round must be a positive integer".to_string()))?;
+                Game::Slots(round)
+            }
+            _ => return Err(Error::new("unknown_game", format!("unknown game {}", game))),
+        })
+    }
+
+    // results of every nonce serialized at once, or of the single nonce if `batch` is false
+    fn simulate<R: JsCast>(
+        &self,
+        client_seed: &str,
+        server_seed: &str,
+        nonces: &[u64],
+        batch: bool,
+    ) -> Result<R, Error> {
+        let run = Run {
+            client_seed,
+            server_seed,
+            nonces,
+            batch,
+        };
+        match *self {
+            Game::Baccarat(dealing) => {
+                run.collect(|config| baccarat::simulate_with_dealing(config, dealing))
+            }
+            Game::Dice => run.collect(dice::simulate),
+            Game::Limbo => run.collect(limbo::simulate),
+            Game::Hilo(dealing) => {
+                run.collect(|config| hilo::simulate_with_dealing(config, dealing))
+            }
+            Game::Blackjack(dealing) => {
+                run.collect(|config| blackjack::simulate_with_dealing(config, dealing))
+            }
+            Game::DiamondPoker => run.collect(diamond_poker::simulate),
+            Game::DragonTower(difficulty) => {
+                run.collect(|config| dragon_tower::simulate(config, difficulty))
+            }
+            Game::Roulette => run.collect(roulette::simulate),
+            Game::Keno => run.collect(keno::simulate),
+            Game::Plinko(opts) => run.collect(|config| plinko::simulate(config, Some(opts))),
+            Game::Mines(mines) => run.collect(|config| mines::simulate(config, mines)),
+            Game::VideoPoker(dealing) => {
+                run.collect(|config| video_poker::simulate_with_dealing(config, dealing))
+            }
+            Game::Wheel(opts) => run.collect(|config| wheel::simulate(config, Some(opts))),
+            Game::Slots(round) => run.collect(|config| slots::simulate(config, round)),
         }
-        "wheel" => {
-            let opts: WheelOpts = opts.into_serde().unwrap();
-            let segments = opts.segments as u8;
-            let risk = wheel::Risk::from_str(&opts.risk);
-            let res = wheel::simulate(config, Some(wheel::Opts::new(segments, risk)));
-            JsValue::from_serde(&res).unwrap()
+    }
+
+    /// The number that sums up a bet (roll, multiplier, pocket or payout), for games that have
+    /// one.
+    fn outcome(&self, config: ProvablyFairConfig) -> Option<f64> {
+        match *self {
+            Game::Dice => Some(dice::simulate(config).outcome),
+            Game::Limbo => Some(limbo::simulate(config).outcome),
+            Game::Roulette => Some(roulette::simulate(config).pocket as f64),
+            Game::Plinko(opts) => Some(plinko::simulate(config, Some(opts)).payout),
+            Game::Wheel(opts) => Some(wheel::simulate(config, Some(opts)).payout),
+            _ => None,
         }
-        "slots" => {
-            let opts: SlotsOpts = opts.into_serde().unwrap();
-            let round = opts.round as usize;
-            let res = slots::simulate(config, round);
-            JsValue::from_serde(&res).unwrap()
+    }
+}
+
+struct Run<'a> {
+    client_seed: &'a str,
+    server_seed: &'a str,
+    nonces: &'a [u64],
+    batch: bool,
+}
+
+impl Run<'_> {
+    fn collect<T: Serialize, R: JsCast>(
+        &self,
+        simulate: impl Fn(ProvablyFairConfig) -> T,
+    ) -> Result<R, Error> {
+        let mut results: Vec<T> = self
+            .nonces
+            .iter()
+            .map(|&nonce| {
+                simulate(ProvablyFairConfig::new(
+                    self.client_seed,
+                    self.server_seed,
+                    nonce,
+                ))
+            })
+            .collect();
+        if self.batch {
+            to_js(&results)
+        } else {
+            to_js(&results.remove(0))
         }
-        _ => unimplemented!(),
+    }
+}
+
+/// `Number.MAX_SAFE_INTEGER`, integers above it can't be represented exactly by a JS number.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.;
+
+// nonces, positions and bounds are JS numbers rather than `u64`, which would have to be passed as
+// a `BigInt`
+fn to_u64(number: f64, kind: &'static str, name: &str) -> Result<u64, Error> {
+    if number.fract() == 0. && (0. ..=MAX_SAFE_INTEGER).contains(&number) {
+        Ok(number as u64)
+    } else {
+        Err(Error::new(
+            kind,
+            format!(
+                "{} must be an integer between 0 and Number.MAX_SAFE_INTEGER",
+                name
+            ),
+        ))
+    }
+}
+
+fn to_nonce(nonce: f64) -> Result<u64, Error> {
+    to_u64(nonce, "invalid_nonce", "nonce")
+}
+
+fn nonce_range(start_nonce: f64, count: u32) -> Result<Vec<u64>, Error> {
+    let start = to_nonce(start_nonce)?;
+    Ok((start..start + count as u64).collect())
+}
+
+#[wasm_bindgen]
+pub fn simulate(
+    game: &str,
+    client_seed: &str,
+    server_seed: &str,
+    nonce: f64,
+    opts: Option<GameOpts>,
+) -> Result<SimulationResult, JsValue> {
+    let game = Game::parse(game, &opts)?;
+    Ok(game.simulate(client_seed, server_seed, &[to_nonce(nonce)?], false)?)
+}
+
+/// Simulates `count` consecutive nonces from `start_nonce` in a single call.
+#[wasm_bindgen]
+pub fn simulate_range(
+    game: &str,
+    client_seed: &str,
+    server_seed: &str,
+    start_nonce: f64,
+    count: u32,
+    opts: Option<GameOpts>,
+) -> Result<SimulationResults, JsValue> {
+    let game = Game::parse(game, &opts)?;
+    let nonces = nonce_range(start_nonce, count)?;
+    Ok(game.simulate(client_seed, server_seed, &nonces, true)?)
+}
+
+/// Simulates a list of nonces in a single call, the results are in the same order.
+#[wasm_bindgen]
+pub fn simulate_nonces(
+    game: &str,
+    client_seed: &str,
+    server_seed: &str,
+    nonces: &[f64],
+    opts: Option<GameOpts>,
+) -> Result<SimulationResults, JsValue> {
+    let game = Game::parse(game, &opts)?;
+    let nonces = nonces
+        .iter()
+        .map(|&nonce| to_nonce(nonce))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(game.simulate(client_seed, server_seed, &nonces, true)?)
+}
+
+/// Only the outcome of each bet of a nonce range, as a `Float64Array` which doesn't need to be
+/// serialized.
+#[wasm_bindgen]
+pub fn outcomes_range(
+    game: &str,
+    client_seed: &str,
+    server_seed: &str,
+    start_nonce: f64,
+    count: u32,
+    opts: Option<GameOpts>,
+) -> Result<Vec<f64>, JsValue> {
+    let game = Game::parse(game, &opts)?;
+    let outcomes = nonce_range(start_nonce, count)?
+        .into_iter()
+        .map(|nonce| {
+            game.outcome(ProvablyFairConfig::new(client_seed, server_seed, nonce))
+                .ok_or_else(|| {
+                    Error::new(
+                        "unsupported_game",
+                        "only dice, limbo, roulette, plinko and wheel have a numeric outcome",
+                    )
+                })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(outcomes)
+}
+
+/// Resolves a player's Dragon Tower climb, `picks` are the tiles picked from the bottom row up.
+#[wasm_bindgen]
+pub fn dragon_tower_climb(
+    client_seed: &str,
+    server_seed: &str,
+    nonce: f64,
+    opts: Option<GameOpts>,
+    picks: &[u8],
+) -> Result<DragonTowerClimb, JsValue> {
+    let difficulty = parse_difficulty(&opts)?;
+    let config = ProvablyFairConfig::new(client_seed, server_seed, to_nonce(nonce)?);
+    let climb = dragon_tower::simulate(config, difficulty)
+        .climb(picks)
+        .map_err(|e| Error::new("invalid_picks", e))?;
+    Ok(to_js(&climb)?)
+}
+
+fn parse_game_hash(game_hash: &str) -> Result<crash::Hash, Error> {
+    game_hash
+        .parse()
+        .map_err(|e| Error::new("invalid_game_hash", e))
+}
+
+fn crash_config(config: &Option<CrashConfig>) -> Result<crash::Config, Error> {
+    let opts: CrashOpts = parse_opts(config.as_deref())?;
+    let stake = crash::Config::for_stake();
+    let parse_hash = |name: &str, hash: Option<String>, default: crash::Hash| match hash {
+        Some(hash) => hash
+            .parse()
+            .map_err(|e| invalid_opts(format!("{}: {}", name, e))),
+        None => Ok(default),
     };
-    result
+    let hash_chain_tip = parse_hash(
+        "hash_chain_tip",
+        opts.hash_chain_tip,
+        stake.hash_chain_tip(),
+    )?;
+    let block_hash = parse_hash("block_hash", opts.block_hash, stake.block_hash())?;
+    let max_chain_length = opts.max_chain_length.unwrap_or(stake.max_chain_length());
+    let house_edge = opts.house_edge.unwrap_or(stake.house_edge());
+    crash::validate_house_edge(house_edge).map_err(invalid_opts)?;
+    Ok(
+        crash::Config::new(hash_chain_tip, block_hash, max_chain_length)
+            .with_house_edge(house_edge),
+    )
 }
 
 #[wasm_bindgen]
-pub fn simulate_multiplayer(game: &str, game_hash: &str, _: JsValue) -> JsValue {
-    let game_hash = crash::Hash::from_hex(game_hash);
-    let config = crash::Config::for_stake();
-    let result = match game {
-        "crash" => {
-            // crash::verify_hash(config, game_hash)
-            let res = crash::simulate(config, game_hash);
-            JsValue::from_serde(&res).unwrap()
-        }
-        _ => unimplemented!(),
+pub fn simulate_multiplayer(
+    game: &str,
+    game_hash: &str,
+    config: Option<CrashConfig>,
+) -> Result<CrashResult, JsValue> {
+    let res = match game {
+        "crash" => to_js(&crash::simulate(
+            crash_config(&config)?,
+            parse_game_hash(game_hash)?,
+        )),
+        _ => Err(Error::new("unknown_game", format!("unknown game {}", game))),
     };
-    result
+    Ok(res?)
 }
 
+/// Walks the whole hash chain before returning, see `CrashVerifier` to keep the page responsive.
 #[wasm_bindgen]
-pub fn verify_game_hash_stake(game_hash: &str) -> bool {
+pub fn verify_game_hash_stake(game_hash: &str) -> Result<bool, JsValue> {
     let config = crash::Config::for_stake();
-    return crash::verify_hash(config, crash::Hash::from_hex(game_hash));
+    Ok(crash::verify_hash(config, parse_game_hash(game_hash)?))
+}
+
+/// Checks that a crash game hash is part of the hash chain a bounded number of hashes at a time,
+/// so it can run across animation frames or in a web worker and report its progress.
+#[wasm_bindgen]
+pub struct CrashVerifier {
+    verifier: crash::Verifier,
+}
+
+#[wasm_bindgen]
+impl CrashVerifier {
+    #[wasm_bindgen(constructor)]
+    pub fn new(game_hash: &str, config: Option<CrashConfig>) -> Result<CrashVerifier, JsValue> {
+        let verifier = crash::Verifier::new(crash_config(&config)?, parse_game_hash(game_hash)?);
+        Ok(CrashVerifier { verifier })
+    }
+
+    /// Walks at most `steps` more hashes, returns the result once it is known and `undefined`
+    /// until then.
+    pub fn step(&mut self, steps: u32) -> Option<bool> {
+        self.verifier.step(steps as usize)
+    }
+
+    /// Number of hashes walked so far.
+    #[wasm_bindgen(getter)]
+    pub fn walked(&self) -> usize {
+        self.verifier.progress().walked
+    }
+
+    /// Maximum number of hashes that may have to be walked.
+    #[wasm_bindgen(getter)]
+    pub fn total(&self) -> usize {
+        self.verifier.progress().total
+    }
+
+    /// Whether the game hash is part of the hash chain, `undefined` until it is known.
+    #[wasm_bindgen(getter)]
+    pub fn result(&self) -> Option<bool> {
+        self.verifier.result()
+    }
 }
 
 #[wasm_bindgen]
 pub fn hash_server_seed(server_seed: &str) -> String {
     utils::hash_server_seed(server_seed)
 }
+
+/// Stake's random number generator, for verifying games in JS with the same stream of numbers as
+/// the games of this crate.
+#[wasm_bindgen(js_name = ProvablyFairRNG)]
+pub struct Rng {
+    rng: ProvablyFairRNG<f64>,
+}
+
+#[wasm_bindgen(js_class = ProvablyFairRNG)]
+impl Rng {
+    #[wasm_bindgen(constructor)]
+    pub fn new(client_seed: &str, server_seed: &str, nonce: f64) -> Result<Rng, JsValue> {
+        Ok(Rng {
+            rng: ProvablyFairRNG::new(client_seed, server_seed, to_nonce(nonce)?),
+        })
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        self.rng.next_byte()
+    }
+
+    /// Float in `[0, 1[` made of the next 4 bytes.
+    pub fn next_float(&mut self) -> f64 {
+        self.rng.next_float()
+    }
+
+    /// Integer in `[start, end[`, made of the next float like every game does.
+    pub fn range(&mut self, start: f64, end: f64) -> Result<f64, JsValue> {
+        let start = to_u64(start, "invalid_range", "start")?;
+        let end = to_u64(end, "invalid_range", "end")?;
+        if end <= start {
+            return Err(Error::new("invalid_range", "end must be greater than start").into());
+        }
+        Ok(self.rng.range_u64(start, end) as f64)
+    }
+
+    /// The next `count` bytes, as a `Uint8Array`.
+    pub fn next_bytes(&mut self, count: u32) -> Vec<u8> {
+        (0..count).map(|_| self.rng.next_byte()).collect()
+    }
+
+    /// The next `count` floats, as a `Float64Array`.
+    pub fn next_floats(&mut self, count: u32) -> Vec<f64> {
+        (0..count).map(|_| self.rng.next_float()).collect()
+    }
+
+    /// Moves to the `position`th byte of the stream, e.g. `seek(4)` skips the first float.
+    pub fn seek(&mut self, position: f64) -> Result<(), JsValue> {
+        self.rng
+            .seek(to_u64(position, "invalid_position", "position")?);
+        Ok(())
+    }
+
+    /// Number of bytes consumed so far (4 per float).
+    #[wasm_bindgen(getter)]
+    pub fn position(&self) -> f64 {
+        self.rng.position() as f64
+    }
+}
@@ -0,0 +1,214 @@
+//! Conformance corpus: runs every game against the vectors of `tests/conformance/v1`, see
+//! `tests/conformance/README.md` for the format.
+
+use fair::games::*;
+use fair::ProvablyFairConfig;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Version of the corpus format, and name of its directory.
+const VERSION: u64 = 1;
+
+/// Every game must have a corpus file.
+const GAMES: [&str; 15] = [
+    "baccarat",
+    "blackjack",
+    "crash",
+    "diamond_poker",
+    "dice",
+    "dragon_tower",
+    "hilo",
+    "keno",
+    "limbo",
+    "mines",
+    "plinko",
+    "roulette",
+    "slots",
+    "video_poker",
+    "wheel",
+];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Corpus {
+    version: u64,
+    game: String,
+    vectors: Vec<Vector>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Vector {
+    #[serde(default)]
+    client_seed: String,
+    #[serde(default)]
+    server_seed: String,
+    #[serde(default)]
+    nonce: u64,
+    /// Only used by crash.
+    game_hash: Option<String>,
+    #[serde(default)]
+    opts: Opts,
+    /// Where the vector was captured, e.g. the URL of the operator's verifier.
+    source: Option<String>,
+    /// Set on vectors computed by this crate instead of captured from the operator, which only
+    /// guard against regressions until a captured vector replaces them.
+    #[serde(default)]
+    self_generated: bool,
+    expected: Value,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct Opts {
+    rows: Option<u8>,
+    risk: Option<String>,
+    segments: Option<u8>,
+    mines: Option<u8>,
+    round: Option<usize>,
+    difficulty: Option<String>,
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+fn simulate(game: &str, vector: &Vector) -> Result<Value, String> {
+    let config = ProvablyFairConfig::new(&vector.client_seed, &vector.server_seed, vector.nonce);
+    let opts = &vector.opts;
+    let risk = || match &opts.risk {
+        Some(risk) => risk.parse(),
+        None => Ok(Risk::Low),
+    };
+    match game {
+        "baccarat" => to_json(baccarat::simulate(config)),
+        "blackjack" => to_json(blackjack::simulate(config)),
+        "crash" => {
+            let game_hash = vector.game_hash.as_ref().ok_or("missing game_hash")?;
+            to_json(crash::simulate(
+                crash::Config::for_stake(),
+                game_hash.parse()?,
+            ))
+        }
+        "diamond_poker" => to_json(diamond_poker::simulate(config)),
+        "dice" => to_json(dice::simulate(config)),
+        "dragon_tower" => {
+            let difficulty = match &opts.difficulty {
+                Some(difficulty) => difficulty.parse()?,
+                None => dragon_tower::Difficulty::Easy,
+            };
+            to_json(dragon_tower::simulate(config, difficulty))
+        }
+        "hilo" => to_json(hilo::simulate(config)),
+        "keno" => to_json(keno::simulate(config)),
+        "limbo" => to_json(limbo::simulate(config)),
+        "mines" => {
+            let mines = opts.mines.unwrap_or(3);
+            mines::validate_mines(mines)?;
+            to_json(mines::simulate(config, mines))
+        }
+        "plinko" => {
+            let opts = plinko::Opts::default()
+                .with_rows(opts.rows.unwrap_or(8))?
+                .with_risk(risk()?);
+            to_json(plinko::simulate(config, Some(opts)))
+        }
+        "roulette" => to_json(roulette::simulate(config)),
+        "slots" => to_json(slots::simulate(config, opts.round.unwrap_or(0))),
+        "video_poker" => to_json(video_poker::simulate(config)),
+        "wheel" => {
+            let opts = wheel::Opts::default()
+                .with_segments(opts.segments.unwrap_or(10))?
+                .with_risk(risk()?);
+            to_json(wheel::simulate(config, Some(opts)))
+        }
+        _ => Err(format!("unknown game {}", game)),
+    }
+}
+
+// fields missing from `expected` aren't checked, and `1` is the same number as `1.0`
+fn matches(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Number(expected), Value::Number(actual)) => expected.as_f64() == actual.as_f64(),
+        (Value::Array(expected), Value::Array(actual)) => {
+            expected.len() == actual.len()
+                && expected.iter().zip(actual).all(|(e, a)| matches(e, a))
+        }
+        (Value::Object(expected), Value::Object(actual)) => expected
+            .iter()
+            .all(|(key, e)| actual.get(key).is_some_and(|a| matches(e, a))),
+        _ => expected == actual,
+    }
+}
+
+#[test]
+fn conformance() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/conformance")
+        .join(format!("v{}", VERSION));
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut games = vec![];
+    let mut failures = vec![];
+    for path in &paths {
+        let name = path.file_name().unwrap().to_string_lossy();
+        let corpus: Corpus = serde_json::from_str(&fs::read_to_string(path).unwrap())
+            .unwrap_or_else(|e| panic!("{}: {}", name, e));
+        assert_eq!(corpus.version, VERSION, "{}", name);
+        assert_eq!(format!("{}.json", corpus.game), name);
+        assert!(!corpus.vectors.is_empty(), "{} has no vectors", name);
+
+        for (i, vector) in corpus.vectors.iter().enumerate() {
+            assert!(
+                vector.source.is_some() != vector.self_generated,
+                "{} #{} needs either a source or \"self_generated\": true",
+                name,
+                i
+            );
+            let source = match &vector.source {
+                Some(source) => format!(" ({})", source),
+                None => String::new(),
+            };
+            match simulate(&corpus.game, vector) {
+                Ok(actual) if matches(&vector.expected, &actual) => {}
+                Ok(actual) => failures.push(format!(
+                    "{} #{}{}\n  expected: {}\n    actual: {}",
+                    name, i, source, vector.expected, actual
+                )),
+                Err(e) => failures.push(format!("{} #{}{}\n  error: {}", name, i, source, e)),
+            }
+        }
+        games.push(corpus.game);
+    }
+
+    for game in &GAMES {
+        assert!(games.iter().any(|g| g == game), "no vectors for {}", game);
+    }
+    assert!(
+        failures.is_empty(),
+        "{} vectors failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_matches() {
+    let expected = serde_json::json!({ "payout": 4, "index": 12 });
+    assert!(matches(
+        &expected,
+        &serde_json::json!({ "payout": 4.0, "index": 12, "path": [] })
+    ));
+    assert!(!matches(&expected, &serde_json::json!({ "payout": 4.0 })));
+    assert!(!matches(
+        &serde_json::json!([1, 2]),
+        &serde_json::json!([1, 2, 3])
+    ));
+}
@@ -1,14 +1,355 @@
-//! TODO
-//! Test suite for the Web and headless browsers.
+//! Test suite for the wasm exports, runs under Node with
+//! `cargo test --target wasm32-unknown-unknown --test web`.
+//!
+//! Every export is called with the same seeds and nonces as the native unit tests and must return
+//! the same JSON as the native functions.
 
 #![cfg(target_arch = "wasm32")]
+// TODO: migrate to serde-wasm-bindgen
+#![allow(deprecated)]
 
-extern crate wasm_bindgen_test;
+use fair::games::*;
+use fair::wasm;
+use fair::{utils, ProvablyFairConfig, ProvablyFairRNG};
+use serde::Serialize;
+use serde_json::{json, Value};
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_test::*;
 
-wasm_bindgen_test_configure!(run_in_browser);
+const CLIENT_SEED: &str = "client seed";
+const SERVER_SEED: &str = "server seed";
+// the last two can't be passed as a `u32`
+const NONCES: [u64; 6] = [0, 1, 2, 42, u32::MAX as u64 + 1, 9_007_199_254_740_991];
+
+fn to_json(value: &JsValue) -> Value {
+    value.into_serde().unwrap()
+}
+
+// native result as JS sees it, e.g. a payout of `4.0` is the number `4`
+fn native<T: Serialize>(res: T) -> Value {
+    to_json(&JsValue::from_serde(&res).unwrap())
+}
+
+fn from_json<T: JsCast>(value: Value) -> Option<T> {
+    Some(JsValue::from_serde(&value).unwrap().unchecked_into())
+}
+
+fn error_kind(err: JsValue) -> String {
+    to_json(&err)["kind"].as_str().unwrap().to_string()
+}
+
+fn config(nonce: u64) -> ProvablyFairConfig {
+    ProvablyFairConfig::new(CLIENT_SEED, SERVER_SEED, nonce)
+}
+
+type Native = fn(ProvablyFairConfig) -> Value;
+
+// every game, with the options passed to wasm and the equivalent native simulation
+fn games() -> Vec<(&'static str, Value, Native)> {
+    vec![
+        ("baccarat", json!({}), |c| native(baccarat::simulate(c))),
+        ("blackjack", json!({}), |c| native(blackjack::simulate(c))),
+        ("blackjack", json!({ "decks": 6 }), |c| {
+            native(blackjack::simulate_with_dealing(c, Dealing::Shoe(6)))
+        }),
+        ("diamond_poker", json!({}), |c| {
+            native(diamond_poker::simulate(c))
+        }),
+        ("dice", json!({}), |c| native(dice::simulate(c))),
+        ("dragon_tower", json!({ "difficulty": "expert" }), |c| {
+            native(dragon_tower::simulate(c, dragon_tower::Difficulty::Expert))
+        }),
+        ("hilo", json!({}), |c| native(hilo::simulate(c))),
+        ("keno", json!({}), |c| native(keno::simulate(c))),
+        ("limbo", json!({}), |c| native(limbo::simulate(c))),
+        ("mines", json!({ "mines": 5 }), |c| {
+            native(mines::simulate(c, 5))
+        }),
+        ("plinko", json!({ "rows": 16, "risk": "high" }), |c| {
+            let opts = plinko::Opts::default()
+                .with_rows(16)
+                .unwrap()
+                .with_risk(Risk::High);
+            native(plinko::simulate(c, Some(opts)))
+        }),
+        ("roulette", json!({}), |c| native(roulette::simulate(c))),
+        ("slots", json!({ "round": 2 }), |c| {
+            native(slots::simulate(c, 2))
+        }),
+        ("video_poker", json!({}), |c| {
+            native(video_poker::simulate(c))
+        }),
+        ("wheel", json!({ "segments": 30, "risk": "medium" }), |c| {
+            let opts = wheel::Opts::default()
+                .with_segments(30)
+                .unwrap()
+                .with_risk(Risk::Medium);
+            native(wheel::simulate(c, Some(opts)))
+        }),
+    ]
+}
 
 #[wasm_bindgen_test]
-fn pass() {
-    assert_eq!(1 + 1, 2);
+fn simulate_matches_native() {
+    for (game, opts, simulate) in games() {
+        for &nonce in &NONCES {
+            let res = wasm::simulate(
+                game,
+                CLIENT_SEED,
+                SERVER_SEED,
+                nonce as f64,
+                from_json(opts.clone()),
+            )
+            .unwrap();
+            assert_eq!(to_json(&res), simulate(config(nonce)), "{} {}", game, nonce);
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn simulate_golden_vectors() {
+    let dice = wasm::simulate("dice", CLIENT_SEED, SERVER_SEED, 1., None).unwrap();
+    assert_eq!(to_json(&dice), json!({ "outcome": 74.67 }));
+
+    let baccarat = wasm::simulate("baccarat", CLIENT_SEED, SERVER_SEED, 1., None).unwrap();
+    let baccarat = to_json(&baccarat);
+    assert_eq!(baccarat["outcome"], "Player");
+    assert_eq!(baccarat["totals"], json!({ "player": 9, "banker": 5 }));
+}
+
+#[wasm_bindgen_test]
+fn batches_match_simulate() {
+    let start = u32::MAX as u64 - 1;
+    for (game, opts, simulate) in games() {
+        let expected: Vec<Value> = (start..start + 4)
+            .map(|nonce| simulate(config(nonce)))
+            .collect();
+
+        let range = wasm::simulate_range(
+            game,
+            CLIENT_SEED,
+            SERVER_SEED,
+            start as f64,
+            4,
+            from_json(opts.clone()),
+        )
+        .unwrap();
+        assert_eq!(to_json(&range), json!(expected), "{}", game);
+
+        let nonces = [start as f64 + 3., start as f64];
+        let list = wasm::simulate_nonces(
+            game,
+            CLIENT_SEED,
+            SERVER_SEED,
+            &nonces,
+            from_json(opts.clone()),
+        )
+        .unwrap();
+        assert_eq!(
+            to_json(&list),
+            json!([expected[3], expected[0]]),
+            "{}",
+            game
+        );
+
+        let outcomes = wasm::outcomes_range(
+            game,
+            CLIENT_SEED,
+            SERVER_SEED,
+            start as f64,
+            4,
+            from_json(opts),
+        );
+        match game {
+            "dice" | "limbo" | "plinko" | "roulette" | "wheel" => {
+                let expected: Vec<f64> = expected
+                    .iter()
+                    .map(|res| {
+                        let outcome = res.get("outcome").or_else(|| res.get("pocket"));
+                        outcome.unwrap_or(&res["payout"]).as_f64().unwrap()
+                    })
+                    .collect();
+                assert_eq!(outcomes.unwrap(), expected, "{}", game);
+            }
+            _ => assert_eq!(
+                error_kind(outcomes.err().unwrap()),
+                "unsupported_game",
+                "{}",
+                game
+            ),
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn invalid_input() {
+    for &nonce in &[-1., 1.5, 9_007_199_254_740_992., f64::NAN] {
+        let err = wasm::simulate("dice", CLIENT_SEED, SERVER_SEED, nonce, None)
+            .err()
+            .unwrap();
+        assert_eq!(error_kind(err), "invalid_nonce", "{}", nonce);
+    }
+    let err = wasm::simulate_nonces("dice", CLIENT_SEED, SERVER_SEED, &[1., -1.], None)
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(err), "invalid_nonce");
+
+    let err = wasm::simulate("poker", CLIENT_SEED, SERVER_SEED, 1., None)
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(err), "unknown_game");
+    let opts = from_json(json!({ "rows": 20 }));
+    let err = wasm::simulate("plinko", CLIENT_SEED, SERVER_SEED, 1., opts)
+        .err()
+        .unwrap();
+    assert_eq!(
+        to_json(&err),
+        json!({ "kind": "invalid_opts", "message": "rows must be between 8 and 16 inclusive" })
+    );
+}
+
+#[wasm_bindgen_test]
+fn dragon_tower_climb() {
+    let opts = || from_json(json!({ "difficulty": "hard" }));
+    for &nonce in &NONCES {
+        let res = dragon_tower::simulate(config(nonce), dragon_tower::Difficulty::Hard);
+        let picks = [1, 0, 1];
+        let climb =
+            wasm::dragon_tower_climb(CLIENT_SEED, SERVER_SEED, nonce as f64, opts(), &picks)
+                .unwrap();
+        assert_eq!(to_json(&climb), native(res.climb(&picks).unwrap()));
+    }
+
+    let err = wasm::dragon_tower_climb(CLIENT_SEED, SERVER_SEED, 1., opts(), &[2])
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(err), "invalid_picks");
+    let opts = from_json(json!({ "difficulty": "extreme" }));
+    let err = wasm::dragon_tower_climb(CLIENT_SEED, SERVER_SEED, 1., opts, &[0])
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(err), "invalid_opts");
+}
+
+#[wasm_bindgen_test]
+fn crash_matches_native() {
+    let game_hash = "5844bf329a6334074778ab8a5f0960e24f9eec43f83bbd98ac0a9f8bcd87184e";
+    let hash = game_hash.parse().unwrap();
+
+    let res = wasm::simulate_multiplayer("crash", game_hash, None).unwrap();
+    assert_eq!(
+        to_json(&res),
+        native(crash::simulate(crash::Config::for_stake(), hash))
+    );
+
+    let config = crash::Config::for_stake().with_house_edge(0.03);
+    let res =
+        wasm::simulate_multiplayer("crash", game_hash, from_json(json!({ "house_edge": 0.03 })))
+            .unwrap();
+    assert_eq!(to_json(&res), native(crash::simulate(config, hash)));
+
+    let err = wasm::simulate_multiplayer("crash", "5844", None)
+        .err()
+        .unwrap();
+    assert_eq!(error_kind(err), "invalid_game_hash");
+    assert!(wasm::verify_game_hash_stake(game_hash).unwrap());
+    let err = wasm::verify_game_hash_stake("5844").err().unwrap();
+    assert_eq!(error_kind(err), "invalid_game_hash");
+}
+
+#[wasm_bindgen_test]
+fn crash_verifier() {
+    let chain: Vec<_> = crash::HashChain::new(
+        "5844bf329a6334074778ab8a5f0960e24f9eec43f83bbd98ac0a9f8bcd87184e"
+            .parse()
+            .unwrap(),
+    )
+    .take(10)
+    .collect();
+    let config = json!({ "hash_chain_tip": chain[9].to_string(), "max_chain_length": 10 });
+
+    let mut verifier =
+        wasm::CrashVerifier::new(&chain[0].to_string(), from_json(config.clone())).unwrap();
+    assert_eq!((verifier.walked(), verifier.total()), (0, 10));
+    assert_eq!(verifier.step(6), None);
+    assert_eq!(verifier.result(), None);
+    assert_eq!(verifier.step(6), Some(true));
+    assert_eq!((verifier.walked(), verifier.result()), (10, Some(true)));
+
+    let bad_hash = "deadbeefe7c270724bd4851c020d489257fa79a70e694a9b5099375464348698";
+    let mut verifier = wasm::CrashVerifier::new(bad_hash, from_json(config)).unwrap();
+    assert_eq!(verifier.step(100), Some(false));
+
+    let config = from_json(json!({ "house_edge": 1 }));
+    let err = wasm::CrashVerifier::new(bad_hash, config).err().unwrap();
+    assert_eq!(error_kind(err), "invalid_opts");
+}
+
+#[wasm_bindgen_test]
+fn hash_server_seed() {
+    assert_eq!(
+        wasm::hash_server_seed(SERVER_SEED),
+        "a4e53dc2f480b8fce6fe688b1317658b446299df23ad533394406427c8c19557"
+    );
+    assert_eq!(
+        wasm::hash_server_seed("some server seed"),
+        utils::hash_server_seed("some server seed")
+    );
+}
+
+#[wasm_bindgen_test]
+fn rng_matches_native() {
+    for &nonce in &NONCES {
+        let mut rng = wasm::Rng::new(CLIENT_SEED, SERVER_SEED, nonce as f64).unwrap();
+        let mut bytes: ProvablyFairRNG<u8> = ProvablyFairRNG::new(CLIENT_SEED, SERVER_SEED, nonce);
+        let expected: Vec<u8> = bytes.by_ref().take(40).collect();
+        assert_eq!(rng.next_byte(), expected[0]);
+        assert_eq!(rng.next_bytes(39), &expected[1..]);
+        assert_eq!(rng.position(), 40.);
+
+        let mut floats: ProvablyFairRNG<f64> =
+            ProvablyFairRNG::new(CLIENT_SEED, SERVER_SEED, nonce);
+        let expected: Vec<f64> = floats.by_ref().take(10).collect();
+        rng.seek(0.).unwrap();
+        assert_eq!(rng.next_float(), expected[0]);
+        assert_eq!(rng.next_floats(8), &expected[1..9]);
+        assert_eq!(
+            rng.range(10., 20.).unwrap() as usize,
+            (expected[9] * 10.) as usize + 10
+        );
+    }
+
+    // vectors of the native rng tests
+    let mut rng = wasm::Rng::new("some client seed", "some server seed", 1.).unwrap();
+    assert_eq!(rng.next_bytes(5), [151, 136, 121, 135, 209]);
+    rng.seek(0.).unwrap();
+    assert_eq!(rng.next_floats(2), [0.5919261889066547, 0.81884371698834]);
+
+    // positions and bounds past u32::MAX
+    let position = 2_f64.powi(40) + 4.;
+    rng.seek(position).unwrap();
+    let mut native: ProvablyFairRNG<f64> =
+        ProvablyFairRNG::new("some client seed", "some server seed", 1);
+    native.seek(position as u64);
+    assert_eq!(rng.next_float(), native.next().unwrap());
+    assert_eq!(rng.position(), position + 4.);
+    let start = 2_f64.powi(33);
+    let end = start + 2_f64.powi(34);
+    let expected = native.range_u64(start as u64, end as u64) as f64;
+    assert_eq!(rng.range(start, end).unwrap(), expected);
+    assert!(expected >= start && expected < end);
+
+    assert_eq!(
+        error_kind(rng.range(5., 5.).err().unwrap()),
+        "invalid_range"
+    );
+    assert_eq!(
+        error_kind(rng.range(-1., 5.).err().unwrap()),
+        "invalid_range"
+    );
+    assert_eq!(error_kind(rng.seek(0.5).err().unwrap()), "invalid_position");
+    let err = rng.seek(2_f64.powi(53)).err().unwrap();
+    assert_eq!(error_kind(err), "invalid_position");
+    let err = wasm::Rng::new(CLIENT_SEED, SERVER_SEED, -1.).err().unwrap();
+    assert_eq!(error_kind(err), "invalid_nonce");
 }